@@ -0,0 +1,110 @@
+//! Parser throughput on synthetic exports the size of a busy Jita item
+//! (tens of thousands of orders, several megabytes).
+//!
+//! `parse_then_summarize` is the file watcher's path, which keeps the order book for
+//! the diff and the station breakdown; `summarize_market_log` is the single streaming
+//! pass `market_analyzer` uses. Both give the same `MarketData`, which the parser's
+//! unit tests check.
+//!
+//! Run with `cargo bench --bench market_parser`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use market_toolbox_lib::market_parser::{self, PriceEstimator};
+use market_toolbox_lib::profile::Profile;
+use market_toolbox_lib::station_groups::StationGroups;
+use std::fmt::Write;
+
+const FILE_NAME: &str = "The Forge-Tritanium-2026.10.17 120000.txt";
+const HEADER: &str = "price,volRemaining,typeID,range,orderID,volEntered,minVolume,bid,issueDate,duration,stationID,regionID,solarSystemID,jumps,";
+
+/// Builds a market log with `rows` orders, half of them bids, spread over 40 stations.
+fn market_log(rows: usize) -> String {
+    let mut csv = String::with_capacity(rows * 100);
+    csv.push_str(HEADER);
+    csv.push('\n');
+
+    // Small LCG so every run benchmarks the same file
+    let mut seed: u64 = 42;
+    for i in 0..rows {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let noise = (seed >> 33) as f64 / (1u64 << 31) as f64;
+        let bid = i % 2 == 0;
+        let price = if bid { 4.0 + noise } else { 5.0 + noise };
+        let station = i % 40;
+
+        writeln!(
+            csv,
+            "{:.2},{}.0,34,{},{},{},{},{},2026-10-{:02} 10:00:00.000,90,{},10000002,{},{},",
+            price,
+            1000 + i % 5000,
+            if bid { 5 } else { 32767 },
+            6_800_000_000u64 + i as u64,
+            10_000,
+            1 + i % 3,
+            if bid { "True" } else { "False" },
+            1 + i % 17,
+            60_003_000 + station,
+            30_000_100 + station,
+            station % 9,
+        )
+        .unwrap();
+    }
+    csv
+}
+
+fn profile() -> Profile {
+    let mut profile = Profile::default("Bench".to_string());
+    profile.buy_range = 4;
+    profile.sell_range = 4;
+    profile.price_estimator = PriceEstimator::MedianAbsoluteDeviation { threshold: 3.0 };
+    profile.max_order_age_days = Some(14.0);
+    profile
+}
+
+fn bench_market_parser(c: &mut Criterion) {
+    let profile = profile();
+    let station_groups = StationGroups::default();
+
+    let mut group = c.benchmark_group("market_parser");
+    group.sample_size(20);
+
+    for rows in [10_000, 50_000] {
+        let csv = market_log(rows);
+        group.throughput(Throughput::Bytes(csv.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("parse_market_log", rows), &csv, |b, csv| {
+            b.iter(|| market_parser::parse_market_log(black_box(csv), FILE_NAME))
+        });
+
+        let book = market_parser::parse_market_log(&csv, FILE_NAME).0.unwrap();
+        group.bench_with_input(BenchmarkId::new("summarize_order_book", rows), &book, |b, book| {
+            b.iter(|| market_parser::summarize_order_book(black_box(book), &profile, &station_groups))
+        });
+
+        // The two ways of getting from an export to `MarketData`, on the same input
+        group.bench_with_input(BenchmarkId::new("parse_then_summarize", rows), &csv, |b, csv| {
+            b.iter(|| {
+                let book = market_parser::parse_market_log(black_box(csv), FILE_NAME).0.unwrap();
+                market_parser::summarize_order_book(&book, &profile, &station_groups)
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("summarize_market_log", rows), &csv, |b, csv| {
+            b.iter(|| {
+                market_parser::summarize_market_log(
+                    black_box(csv.as_bytes()),
+                    FILE_NAME,
+                    &profile,
+                    &station_groups,
+                )
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_market_parser);
+criterion_main!(benches);
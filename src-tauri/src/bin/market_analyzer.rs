@@ -0,0 +1,228 @@
+//! Analyzes market log exports without the GUI, using the same profiles and
+//! station groups as the app.
+//!
+//! ```text
+//! market_analyzer [--profile NAME | --profile-file PATH] [--data-dir DIR] [--json] FILE...
+//! ```
+
+use anyhow::{anyhow, Context};
+use market_toolbox_lib::market_parser::{self, MarketData, ParseReport};
+use market_toolbox_lib::profile::Profile;
+use market_toolbox_lib::settings::AppSettings;
+use market_toolbox_lib::station_groups::StationGroups;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: market_analyzer [OPTIONS] FILE...
+
+Options:
+  --profile NAME       Profile saved in the app (default: the app's selected profile)
+  --profile-file PATH  Profile JSON file, e.g. one copied from another machine
+  --data-dir DIR       App data directory (default: the app's own)
+  --json               Print JSON instead of a table
+  -h, --help           Show this help";
+
+struct Args {
+    files: Vec<PathBuf>,
+    profile_name: Option<String>,
+    profile_file: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+    json: bool,
+}
+
+impl Args {
+    fn parse() -> anyhow::Result<Option<Self>> {
+        let mut args = Args {
+            files: Vec::new(),
+            profile_name: None,
+            profile_file: None,
+            data_dir: None,
+            json: false,
+        };
+
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            let mut value = || iter.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--json" => args.json = true,
+                "--profile" => args.profile_name = Some(value()?),
+                "--profile-file" => args.profile_file = Some(PathBuf::from(value()?)),
+                "--data-dir" => args.data_dir = Some(PathBuf::from(value()?)),
+                _ if arg.starts_with("--") => return Err(anyhow!("Unknown option {}", arg)),
+                _ => args.files.push(PathBuf::from(arg)),
+            }
+        }
+
+        if args.files.is_empty() {
+            return Err(anyhow!("No market log files given"));
+        }
+        if args.profile_name.is_some() && args.profile_file.is_some() {
+            return Err(anyhow!("Use either --profile or --profile-file, not both"));
+        }
+        Ok(Some(args))
+    }
+}
+
+fn load_profile(args: &Args, app_data_dir: &PathBuf) -> anyhow::Result<Profile> {
+    if let Some(path) = &args.profile_file {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        return serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse profile {}", path.display()));
+    }
+
+    let profile_name = match &args.profile_name {
+        Some(name) => name.clone(),
+        None => AppSettings::load(app_data_dir)
+            .map(|settings| settings.selected_profile)
+            .unwrap_or_else(|_| "Default".to_string()),
+    };
+
+    // Profile::load falls back to defaults for unknown names, which would hide a typo
+    if !Profile::list_all(app_data_dir)?.contains(&profile_name) {
+        return Err(anyhow!(
+            "No profile named '{}' in {}",
+            profile_name,
+            app_data_dir.display()
+        ));
+    }
+    Profile::load(app_data_dir, &profile_name)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileResult {
+    file: String,
+    market_data: Option<MarketData>,
+    report: ParseReport,
+}
+
+fn analyze(path: &PathBuf, profile: &Profile, station_groups: &StationGroups) -> FileResult {
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_string();
+
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return FileResult {
+                file: path.display().to_string(),
+                market_data: None,
+                report: ParseReport {
+                    file_name,
+                    error: Some(format!("Failed to read file: {}", e)),
+                    ..ParseReport::default()
+                },
+            }
+        }
+    };
+
+    // Nothing here needs the individual orders, so skip building the order book
+    let (content, encoding) = market_parser::decode_market_log(&bytes);
+    let (result, mut report) = market_parser::summarize_market_log(
+        content.as_bytes(),
+        &file_name,
+        profile,
+        station_groups,
+    );
+    if encoding != market_parser::LogEncoding::Utf8 {
+        report.warnings.push(format!("Read as {}", encoding));
+    }
+
+    FileResult {
+        file: path.display().to_string(),
+        market_data: result.ok(),
+        report,
+    }
+}
+
+fn format_price(price: f64) -> String {
+    if price < 0.0 {
+        "-".to_string()
+    } else {
+        format!("{:.2}", price)
+    }
+}
+
+fn print_table(results: &[FileResult], profile: &Profile) {
+    println!("Profile: {}", profile.profile_name);
+    println!(
+        "{:<32} {:<14} {:>14} {:>14} {:>14} {:>14} {:>12} {:>9} {:>11}",
+        "Item", "Region", "Sell", "Buy", "Undercut", "Overbid", "Profit", "Margin", "Orders S/B"
+    );
+
+    for result in results {
+        let Some(data) = &result.market_data else {
+            eprintln!(
+                "{}: {}",
+                result.file,
+                result.report.error.as_deref().unwrap_or("no orders")
+            );
+            continue;
+        };
+
+        println!(
+            "{:<32} {:<14} {:>14} {:>14} {:>14} {:>14} {:>12.2} {:>8.2}% {:>11}",
+            data.item_name,
+            data.region.as_deref().unwrap_or("-"),
+            format_price(data.sell_price),
+            format_price(data.buy_price),
+            format_price(data.suggested_sell_price),
+            format_price(data.suggested_buy_price),
+            data.calculated.profit,
+            data.calculated.margin,
+            format!("{}/{}", data.sell_order_count, data.buy_order_count),
+        );
+        for warning in &result.report.warnings {
+            eprintln!("{}: {}", result.file, warning);
+        }
+        if result.report.skipped_rows > 0 {
+            eprintln!("{}: skipped {} rows", result.file, result.report.skipped_rows);
+        }
+    }
+}
+
+fn run() -> anyhow::Result<bool> {
+    let Some(args) = Args::parse()? else {
+        println!("{}", USAGE);
+        return Ok(true);
+    };
+
+    let app_data_dir = args
+        .data_dir
+        .clone()
+        .unwrap_or_else(market_toolbox_lib::default_app_data_dir);
+    let profile = load_profile(&args, &app_data_dir)?;
+    let station_groups = StationGroups::load(&app_data_dir)
+        .context("Failed to load station groups")?;
+
+    let results: Vec<FileResult> = args
+        .files
+        .iter()
+        .map(|path| analyze(path, &profile, &station_groups))
+        .collect();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        print_table(&results, &profile);
+    }
+
+    Ok(results.iter().all(|result| result.market_data.is_some()))
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("Error: {:#}\n\n{}", e, USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -1,7 +1,19 @@
+use crate::file_watcher::{WatcherControl, WatcherStatus};
+use crate::log_discovery::{self, LogDirCandidate};
+use crate::market_parser::{
+    self, default_depth_target, default_min_volume_handling, default_price_estimator,
+    default_stale_after_minutes, default_units_to_sell, ArbitrageOpportunity, DepthPrices,
+    DepthTarget, MarketData, MinVolumeHandling, OrderBook, OrderReachability, PriceEstimator,
+    StationSummary,
+};
+use crate::pricing::{self, CalculatedData};
 use crate::profile::Profile;
-use crate::settings::AppSettings;
+use crate::session::{MarketSession, SessionEntry};
+use crate::settings::{AppSettings, WatchedDir};
+use crate::station_groups::{default_hub_group, StationGroup, StationGroups};
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, Manager, State};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -22,6 +34,24 @@ pub struct ProfileDto {
     pub sell_custom_broker: f64,
     pub buy_range: u8,
     pub sell_range: u8,
+    #[serde(default = "default_depth_target")]
+    pub depth_target: DepthTarget,
+    #[serde(default = "default_price_estimator")]
+    pub price_estimator: PriceEstimator,
+    #[serde(default = "default_hub_group")]
+    pub buy_hub_group: String,
+    #[serde(default = "default_hub_group")]
+    pub sell_hub_group: String,
+    #[serde(default)]
+    pub sell_station_id: Option<i64>,
+    #[serde(default = "default_units_to_sell")]
+    pub units_to_sell: f64,
+    #[serde(default = "default_min_volume_handling")]
+    pub min_volume_handling: MinVolumeHandling,
+    #[serde(default)]
+    pub max_order_age_days: Option<f64>,
+    #[serde(default = "default_stale_after_minutes")]
+    pub stale_after_minutes: f64,
 }
 
 impl From<Profile> for ProfileDto {
@@ -41,6 +71,15 @@ impl From<Profile> for ProfileDto {
             sell_custom_broker: profile.sell_custom_broker,
             buy_range: profile.buy_range,
             sell_range: profile.sell_range,
+            depth_target: profile.depth_target,
+            price_estimator: profile.price_estimator,
+            buy_hub_group: profile.buy_hub_group,
+            sell_hub_group: profile.sell_hub_group,
+            sell_station_id: profile.sell_station_id,
+            units_to_sell: profile.units_to_sell,
+            min_volume_handling: profile.min_volume_handling,
+            max_order_age_days: profile.max_order_age_days,
+            stale_after_minutes: profile.stale_after_minutes,
         }
     }
 }
@@ -62,6 +101,15 @@ impl From<ProfileDto> for Profile {
             sell_custom_broker: dto.sell_custom_broker,
             buy_range: dto.buy_range,
             sell_range: dto.sell_range,
+            depth_target: dto.depth_target,
+            price_estimator: dto.price_estimator,
+            buy_hub_group: dto.buy_hub_group,
+            sell_hub_group: dto.sell_hub_group,
+            sell_station_id: dto.sell_station_id,
+            units_to_sell: dto.units_to_sell,
+            min_volume_handling: dto.min_volume_handling,
+            max_order_age_days: dto.max_order_age_days,
+            stale_after_minutes: dto.stale_after_minutes,
         }
     }
 }
@@ -92,6 +140,13 @@ pub struct AppSettingsDto {
     pub window_width: Option<u32>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub window_height: Option<u32>,
+    // Edited by hand in settings.json, so only changed when the caller sends them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scan_lookback_hours: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub force_polling: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll_interval_secs: Option<f64>,
 }
 
 impl From<AppSettings> for AppSettingsDto {
@@ -104,20 +159,9 @@ impl From<AppSettings> for AppSettingsDto {
             window_y: settings.window_y,
             window_width: settings.window_width,
             window_height: settings.window_height,
-        }
-    }
-}
-
-impl From<AppSettingsDto> for AppSettings {
-    fn from(dto: AppSettingsDto) -> Self {
-        AppSettings {
-            selected_profile: dto.selected_profile,
-            auto_copy_enabled: dto.auto_copy_enabled,
-            auto_copy_mode: dto.auto_copy_mode,
-            window_x: dto.window_x,
-            window_y: dto.window_y,
-            window_width: dto.window_width,
-            window_height: dto.window_height,
+            scan_lookback_hours: Some(settings.scan_lookback_hours),
+            force_polling: Some(settings.force_polling),
+            poll_interval_secs: Some(settings.poll_interval_secs),
         }
     }
 }
@@ -131,12 +175,39 @@ pub async fn load_settings(app: AppHandle) -> Result<AppSettingsDto, String> {
 }
 
 #[tauri::command]
-pub async fn save_settings(app: AppHandle, settings: AppSettingsDto) -> Result<(), String> {
+pub async fn save_settings(
+    app: AppHandle,
+    settings: AppSettingsDto,
+    watcher: State<'_, WatcherControl>,
+) -> Result<(), String> {
     let app_data_dir = get_app_data_dir(&app)?;
-    let settings_rust: AppSettings = settings.into();
+    // Only overwrite what the caller edits: the window position is saved on close, log
+    // directories through their own commands, and the rest may be edited by hand
+    let existing = AppSettings::load(&app_data_dir)
+        .map_err(|e| format!("Failed to load settings: {}", e))?;
+    let mut settings_rust = existing.clone();
+    settings_rust.selected_profile = settings.selected_profile;
+    settings_rust.auto_copy_enabled = settings.auto_copy_enabled;
+    settings_rust.auto_copy_mode = settings.auto_copy_mode;
+    if let Some(scan_lookback_hours) = settings.scan_lookback_hours {
+        settings_rust.scan_lookback_hours = scan_lookback_hours;
+    }
+    if let Some(force_polling) = settings.force_polling {
+        settings_rust.force_polling = force_polling;
+    }
+    if let Some(poll_interval_secs) = settings.poll_interval_secs {
+        settings_rust.poll_interval_secs = poll_interval_secs;
+    }
     settings_rust
         .save(&app_data_dir)
         .map_err(|e| format!("Failed to save settings: {}", e))?;
+    // The watcher only reads its settings when it is (re)started
+    if settings_rust.force_polling != existing.force_polling
+        || settings_rust.poll_interval_secs != existing.poll_interval_secs
+        || settings_rust.scan_lookback_hours != existing.scan_lookback_hours
+    {
+        watcher.restart();
+    }
     Ok(())
 }
 
@@ -200,7 +271,11 @@ pub async fn delete_profile(app: AppHandle, profile_name: String) -> Result<(),
 }
 
 #[tauri::command]
-pub async fn select_log_path(app: AppHandle) -> Result<(), String> {
+pub async fn select_log_path(
+    app: AppHandle,
+    log_dir: State<'_, Arc<RwLock<PathBuf>>>,
+    watcher: State<'_, WatcherControl>,
+) -> Result<(), String> {
     use std::sync::mpsc;
     use tauri_plugin_dialog::{DialogExt, FilePath};
 
@@ -217,15 +292,258 @@ pub async fn select_log_path(app: AppHandle) -> Result<(), String> {
         .map_err(|e| format!("Task join error: {}", e))?
         .map_err(|e| format!("Dialog error: {}", e))?;
 
-    if let Some(path) = dialog_result {
-        // Update the log directory
-        // This would need to be stored and passed to the watcher
-        // For now, we'll just show a message
-        if let Some(path_ref) = path.as_path() {
-            app.emit("status-update", format!("Log path: {}", path_ref.display()))
-                .map_err(|e| format!("Failed to emit event: {}", e))?;
-        }
+    let Some(path) = dialog_result.and_then(|path| path.into_path().ok()) else {
+        return Ok(());
+    };
+    apply_log_path(&app, path, &log_dir, &watcher).await
+}
+
+/// Uses `path` as the log directory without a folder picker, e.g. a folder chosen from
+/// `discover_log_dirs` during first-run setup.
+#[tauri::command]
+pub async fn set_log_path(
+    app: AppHandle,
+    path: PathBuf,
+    log_dir: State<'_, Arc<RwLock<PathBuf>>>,
+    watcher: State<'_, WatcherControl>,
+) -> Result<(), String> {
+    if !path.is_dir() {
+        return Err(format!("Not a folder: {}", path.display()));
+    }
+    apply_log_path(&app, path, &log_dir, &watcher).await
+}
+
+/// Saves the log directory and restarts the watcher on it.
+async fn apply_log_path(
+    app: &AppHandle,
+    path: PathBuf,
+    log_dir: &RwLock<PathBuf>,
+    watcher: &WatcherControl,
+) -> Result<(), String> {
+    let app_data_dir = get_app_data_dir(app)?;
+    let mut settings = AppSettings::load(&app_data_dir)
+        .map_err(|e| format!("Failed to load settings: {}", e))?;
+    settings.log_dir = Some(path.clone());
+    settings
+        .save(&app_data_dir)
+        .map_err(|e| format!("Failed to save settings: {}", e))?;
+
+    app.emit("status-update", format!("Log path: {}", path.display()))
+        .map_err(|e| format!("Failed to emit event: {}", e))?;
+    *log_dir.write().await = path;
+    watcher.restart();
+
+    Ok(())
+}
+
+/// Market logs folders of the EVE installs on this machine, most recently used first.
+#[tauri::command]
+pub async fn discover_log_dirs() -> Result<Vec<LogDirCandidate>, String> {
+    tokio::task::spawn_blocking(log_discovery::discover_log_dirs)
+        .await
+        .map_err(|e| format!("Task join error: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_order_book(
+    last_order_book: State<'_, Arc<RwLock<Option<OrderBook>>>>,
+) -> Result<Option<OrderBook>, String> {
+    Ok(last_order_book.read().await.clone())
+}
+
+#[tauri::command]
+pub async fn get_depth_prices(
+    target: DepthTarget,
+    last_order_book: State<'_, Arc<RwLock<Option<OrderBook>>>>,
+    current_profile: State<'_, Arc<RwLock<Profile>>>,
+    station_groups: State<'_, Arc<RwLock<StationGroups>>>,
+) -> Result<Option<DepthPrices>, String> {
+    let profile = current_profile.read().await.clone();
+    let station_groups = station_groups.read().await;
+    Ok(last_order_book.read().await.as_ref().map(|book| {
+        market_parser::calculate_depth_prices(book, &profile, &station_groups, target)
+    }))
+}
+
+#[tauri::command]
+pub async fn check_buy_reachability(
+    station_id: i64,
+    last_order_book: State<'_, Arc<RwLock<Option<OrderBook>>>>,
+) -> Result<Vec<OrderReachability>, String> {
+    Ok(last_order_book
+        .read()
+        .await
+        .as_ref()
+        .map(|book| market_parser::check_buy_reachability(book, station_id))
+        .unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn get_station_breakdown(
+    last_order_book: State<'_, Arc<RwLock<Option<OrderBook>>>>,
+) -> Result<Vec<StationSummary>, String> {
+    Ok(last_order_book
+        .read()
+        .await
+        .as_ref()
+        .map(market_parser::summarize_stations)
+        .unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn find_arbitrage(
+    last_order_book: State<'_, Arc<RwLock<Option<OrderBook>>>>,
+    current_profile: State<'_, Arc<RwLock<Profile>>>,
+) -> Result<Vec<ArbitrageOpportunity>, String> {
+    let profile = current_profile.read().await.clone();
+    Ok(last_order_book
+        .read()
+        .await
+        .as_ref()
+        .map(|book| market_parser::find_arbitrage(book, &profile))
+        .unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn list_station_groups(
+    station_groups: State<'_, Arc<RwLock<StationGroups>>>,
+) -> Result<Vec<StationGroup>, String> {
+    Ok(station_groups.read().await.groups.clone())
+}
+
+#[tauri::command]
+pub async fn save_station_group(
+    app: AppHandle,
+    group: StationGroup,
+    station_groups: State<'_, Arc<RwLock<StationGroups>>>,
+) -> Result<(), String> {
+    let app_data_dir = get_app_data_dir(&app)?;
+    let mut station_groups = station_groups.write().await;
+    station_groups.upsert(group);
+    station_groups
+        .save(&app_data_dir)
+        .map_err(|e| format!("Failed to save station groups: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_station_group(
+    app: AppHandle,
+    group_name: String,
+    station_groups: State<'_, Arc<RwLock<StationGroups>>>,
+) -> Result<(), String> {
+    let app_data_dir = get_app_data_dir(&app)?;
+    let mut station_groups = station_groups.write().await;
+    station_groups
+        .remove(&group_name)
+        .map_err(|e| format!("Failed to delete station group: {}", e))?;
+    station_groups
+        .save(&app_data_dir)
+        .map_err(|e| format!("Failed to save station groups: {}", e))?;
+    Ok(())
+}
+
+/// Fee and profit breakdown for `market_data`, using `profile` when given so unsaved
+/// edits apply, otherwise the current profile.
+#[tauri::command]
+pub async fn calculate_profit(
+    market_data: MarketData,
+    profile: Option<ProfileDto>,
+    current_profile: State<'_, Arc<RwLock<Profile>>>,
+) -> Result<CalculatedData, String> {
+    let profile = match profile {
+        Some(profile) => profile.into(),
+        None => current_profile.read().await.clone(),
+    };
+    Ok(pricing::calculate(&market_data, &profile))
+}
+
+#[tauri::command]
+pub async fn get_session(
+    session: State<'_, Arc<RwLock<MarketSession>>>,
+) -> Result<Vec<SessionEntry>, String> {
+    Ok(session.read().await.entries.clone())
+}
+
+#[tauri::command]
+pub async fn list_watched_dirs(
+    watched_dirs: State<'_, Arc<RwLock<Vec<WatchedDir>>>>,
+) -> Result<Vec<WatchedDir>, String> {
+    Ok(watched_dirs.read().await.clone())
+}
+
+#[tauri::command]
+pub async fn save_watched_dir(
+    app: AppHandle,
+    dir: WatchedDir,
+    watched_dirs: State<'_, Arc<RwLock<Vec<WatchedDir>>>>,
+    watcher: State<'_, WatcherControl>,
+) -> Result<(), String> {
+    let mut watched_dirs = watched_dirs.write().await;
+    let mut updated = watched_dirs.clone();
+    match updated.iter_mut().find(|existing| existing.path == dir.path) {
+        Some(existing) => *existing = dir,
+        None => updated.push(dir),
     }
+    save_watched_dirs(&app, &updated)?;
+    *watched_dirs = updated;
+    watcher.restart();
+    Ok(())
+}
 
+#[tauri::command]
+pub async fn delete_watched_dir(
+    app: AppHandle,
+    path: PathBuf,
+    watched_dirs: State<'_, Arc<RwLock<Vec<WatchedDir>>>>,
+    watcher: State<'_, WatcherControl>,
+) -> Result<(), String> {
+    let mut watched_dirs = watched_dirs.write().await;
+    let updated: Vec<WatchedDir> = watched_dirs
+        .iter()
+        .filter(|existing| existing.path != path)
+        .cloned()
+        .collect();
+    save_watched_dirs(&app, &updated)?;
+    *watched_dirs = updated;
+    watcher.restart();
     Ok(())
 }
+
+fn save_watched_dirs(app: &AppHandle, watched_dirs: &[WatchedDir]) -> Result<(), String> {
+    let app_data_dir = get_app_data_dir(app)?;
+    let mut settings = AppSettings::load(&app_data_dir)
+        .map_err(|e| format!("Failed to load settings: {}", e))?;
+    settings.watched_dirs = watched_dirs.to_vec();
+    settings
+        .save(&app_data_dir)
+        .map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+#[tauri::command]
+pub async fn pause_watcher(
+    app: AppHandle,
+    watcher: State<'_, WatcherControl>,
+) -> Result<WatcherStatus, String> {
+    let status = watcher.pause(&app).await;
+    app.emit("status-update", status.message())
+        .map_err(|e| format!("Failed to emit event: {}", e))?;
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn resume_watcher(
+    app: AppHandle,
+    watcher: State<'_, WatcherControl>,
+) -> Result<WatcherStatus, String> {
+    // Still waiting when the log directory is missing
+    let status = watcher.resume(&app).await;
+    app.emit("status-update", status.message())
+        .map_err(|e| format!("Failed to emit event: {}", e))?;
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn watcher_status(watcher: State<'_, WatcherControl>) -> Result<WatcherStatus, String> {
+    Ok(watcher.status().await)
+}
@@ -1,117 +1,1169 @@
-use crate::market_parser;
+use crate::market_diff::OrderBookHistory;
+use crate::market_parser::{
+    self, LogEncoding, MarketData, MarketLogError, MarketLogFileName, OrderBook, ParseReport,
+};
 use crate::profile::Profile;
-use std::path::PathBuf;
+use crate::session::MarketSession;
+use crate::settings::{AppSettings, WatchedDir};
+use crate::station_groups::StationGroups;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::mpsc;
-use std::time::Duration;
-use tauri::{AppHandle, Emitter};
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{Notify, RwLock};
 use tokio::time::sleep;
-use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use notify::{Config, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// How long an export's size and modification time must stay unchanged before it is read.
+const SETTLE_TIME: Duration = Duration::from_millis(500);
+/// How often pending exports are checked for having settled.
+const SETTLE_POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Reads of an export that still looks half-written before it is processed anyway.
+const MAX_READ_ATTEMPTS: u32 = 5;
+/// Processed exports remembered to ignore duplicate events; the oldest are forgotten first.
+const MAX_PROCESSED_EXPORTS: usize = 1000;
+/// How often watched directories are checked for having been deleted or created.
+const DIR_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// The longest wait before watching a directory that failed to be watched is retried.
+const MAX_WATCH_RETRY_DELAY: Duration = Duration::from_secs(300);
+/// Bounds on the polling watcher's interval, whatever settings.json says.
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(3600);
+/// The furthest back the startup scan looks, whatever settings.json says.
+const MAX_SCAN_LOOKBACK: Duration = Duration::from_secs(365 * 24 * 3600);
+
+/// The shared state every processed export reads from and updates.
+#[derive(Clone)]
+pub struct WatcherContext {
+    pub current_profile: Arc<RwLock<Profile>>,
+    pub last_order_book: Arc<RwLock<Option<OrderBook>>>,
+    pub station_groups: Arc<RwLock<StationGroups>>,
+    pub order_book_history: Arc<RwLock<OrderBookHistory>>,
+    pub session: Arc<RwLock<MarketSession>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WatcherState {
+    Starting,
+    /// None of the log directories exist yet.
+    Waiting,
+    Watching,
+    /// Exports are ignored until the watcher is resumed.
+    Paused,
+}
+
+/// What the watcher is doing, for the `watcher_status` command and `watcher-status` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatcherStatus {
+    pub state: WatcherState,
+    pub backend: Option<WatcherBackend>,
+    pub watched_paths: Vec<PathBuf>,
+    pub files_processed: u64,
+    pub last_event_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+impl WatcherStatus {
+    /// A `status-update` line for the current state.
+    pub fn message(&self) -> String {
+        match self.state {
+            WatcherState::Starting => "Starting to watch for market logs...".to_string(),
+            WatcherState::Waiting => "Waiting for market logs directory...".to_string(),
+            WatcherState::Watching if self.watched_paths.len() > 1 => {
+                format!("Watching {} market log folders...", self.watched_paths.len())
+            }
+            WatcherState::Watching => "Watching for market logs...".to_string(),
+            WatcherState::Paused => "Paused, new market logs are ignored".to_string(),
+        }
+    }
+}
+
+impl Default for WatcherStatus {
+    fn default() -> Self {
+        WatcherStatus {
+            state: WatcherState::Starting,
+            backend: None,
+            watched_paths: Vec::new(),
+            files_processed: 0,
+            last_event_at: None,
+            last_error: None,
+        }
+    }
+}
+
+/// Lets commands pause, resume and inspect the running watcher, or tear it down so it
+/// starts over on the current log directories.
+#[derive(Clone, Default)]
+pub struct WatcherControl {
+    restart: Arc<Notify>,
+    paused: Arc<RwLock<bool>>,
+    status: Arc<RwLock<WatcherStatus>>,
+}
+
+impl WatcherControl {
+    pub fn restart(&self) {
+        self.restart.notify_one();
+    }
+
+    pub async fn is_paused(&self) -> bool {
+        *self.paused.read().await
+    }
+
+    /// Stops processing exports; the directories stay watched so resuming is instant.
+    pub async fn pause(&self, app: &AppHandle) -> WatcherStatus {
+        *self.paused.write().await = true;
+        self.update(app, |_| {}).await
+    }
+
+    /// Processes new exports again. Exports made while paused are not replayed.
+    pub async fn resume(&self, app: &AppHandle) -> WatcherStatus {
+        *self.paused.write().await = false;
+        self.update(app, |_| {}).await
+    }
+
+    pub async fn status(&self) -> WatcherStatus {
+        let mut status = self.status.read().await.clone();
+        if self.is_paused().await {
+            status.state = WatcherState::Paused;
+        }
+        status
+    }
+
+    /// Applies `change` to the status and sends the result as a `watcher-status` event.
+    async fn update(
+        &self,
+        app: &AppHandle,
+        change: impl FnOnce(&mut WatcherStatus),
+    ) -> WatcherStatus {
+        change(&mut *self.status.write().await);
+        let status = self.status().await;
+        app.emit("watcher-status", &status).ok();
+        status
+    }
+
+    async fn record_error(&self, app: &AppHandle, error: String) {
+        self.update(app, |status| status.last_error = Some(error))
+            .await;
+    }
+
+    /// Sleeps for `duration`, returning early with `true` when a restart is requested.
+    async fn sleep(&self, duration: Duration) -> bool {
+        tokio::select! {
+            _ = sleep(duration) => false,
+            _ = self.restart.notified() => true,
+        }
+    }
+}
+
+/// Where an export came from: one of the watched directories, and the profile bound to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogSource {
+    pub dir: PathBuf,
+    pub profile: Option<String>,
+}
+
+impl LogSource {
+    fn of<'a>(sources: &'a [LogSource], path: &Path) -> Option<&'a LogSource> {
+        // Events may carry the resolved path of a symlinked or relative directory
+        let dir = canonical(path.parent()?);
+        sources.iter().find(|source| canonical(&source.dir) == dir)
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// The payload of the `market-data`, `parse-report` and `market-diff` events: the
+/// result plus the directory it was exported to.
+#[derive(Clone, Serialize)]
+struct Sourced<'a, T> {
+    #[serde(flatten)]
+    payload: &'a T,
+    source: &'a LogSource,
+}
+
+impl<'a, T: Serialize + Clone> Sourced<'a, T> {
+    fn emit(app: &AppHandle, event: &str, payload: &'a T, source: &'a LogSource) {
+        app.emit(event, Sourced { payload, source }).ok();
+    }
+}
+
+/// The selected log directory first, then the extra ones, each directory once.
+async fn watched_sources(
+    log_dir: &Arc<RwLock<PathBuf>>,
+    watched_dirs: &Arc<RwLock<Vec<WatchedDir>>>,
+) -> Vec<LogSource> {
+    let mut sources = vec![LogSource {
+        dir: log_dir.read().await.clone(),
+        profile: None,
+    }];
+    for watched_dir in watched_dirs.read().await.iter() {
+        if sources.iter().all(|source| source.dir != watched_dir.path) {
+            sources.push(LogSource {
+                dir: watched_dir.path.clone(),
+                profile: watched_dir.profile.clone(),
+            });
+        }
+    }
+    sources
+}
+
+/// How changes in the log directories are noticed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WatcherBackend {
+    /// OS file events: inotify, FSEvents or ReadDirectoryChangesW.
+    Native,
+    /// Rescanning the directories on an interval.
+    Polling,
+}
+
+/// The `watcher-backend` event payload, sent whenever the watcher is (re)armed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatcherBackendStatus {
+    pub backend: WatcherBackend,
+    pub poll_interval_secs: Option<f64>,
+    /// Why OS file events are not used, when polling was not asked for.
+    pub fallback_reason: Option<String>,
+}
+
+fn create_watcher(
+    tx: &mpsc::Sender<notify::Result<notify::Event>>,
+    backend: WatcherBackend,
+    poll_interval: Duration,
+) -> notify::Result<Box<dyn Watcher + Send>> {
+    Ok(match backend {
+        WatcherBackend::Native => Box::new(RecommendedWatcher::new(tx.clone(), Config::default())?),
+        WatcherBackend::Polling => Box::new(PollWatcher::new(
+            tx.clone(),
+            Config::default().with_poll_interval(poll_interval),
+        )?),
+    })
+}
+
+/// The settings the watcher uses, read when it starts and on every restart.
+struct WatcherSettings {
+    force_polling: bool,
+    poll_interval: Duration,
+    /// `None` turns the startup scan off.
+    scan_lookback: Option<chrono::Duration>,
+}
+
+impl WatcherSettings {
+    fn load(app: &AppHandle) -> Self {
+        let settings = app
+            .path()
+            .app_data_dir()
+            .ok()
+            .and_then(|app_data_dir| AppSettings::load(&app_data_dir).ok())
+            .unwrap_or_else(AppSettings::default);
+        Self::from(&settings)
+    }
+}
+
+impl From<&AppSettings> for WatcherSettings {
+    /// Hand-edited values out of range are clamped rather than trusted.
+    fn from(settings: &AppSettings) -> Self {
+        let poll_interval = Duration::try_from_secs_f64(
+            settings
+                .poll_interval_secs
+                .clamp(MIN_POLL_INTERVAL.as_secs_f64(), MAX_POLL_INTERVAL.as_secs_f64()),
+        )
+        // Only NaN gets here
+        .unwrap_or(MIN_POLL_INTERVAL);
+        let scan_lookback = (settings.scan_lookback_hours > 0.0)
+            .then(|| {
+                Duration::try_from_secs_f64(settings.scan_lookback_hours * 3600.0)
+                    .map_or(MAX_SCAN_LOOKBACK, |lookback| lookback.min(MAX_SCAN_LOOKBACK))
+            })
+            .and_then(|lookback| chrono::Duration::from_std(lookback).ok());
+
+        WatcherSettings {
+            force_polling: settings.force_polling,
+            poll_interval,
+            scan_lookback,
+        }
+    }
+}
 
 pub async fn watch_market_logs(
     app: AppHandle,
     log_dir: Arc<RwLock<PathBuf>>,
-    current_profile: Arc<RwLock<Profile>>,
+    watched_dirs: Arc<RwLock<Vec<WatchedDir>>>,
+    context: WatcherContext,
+    control: WatcherControl,
 ) {
-    loop {
-        let current_dir = log_dir.read().await.clone();
+    let mut tracker = ExportTracker::default();
+    let mut settings = WatcherSettings::load(&app);
+    let mut failed_watches = FailedWatches::default();
+    // Set once OS file events failed, until a restart is requested
+    let mut native_failure: Option<String> = None;
+    let mut restart_requested = false;
 
-        if !current_dir.exists() {
-            app.emit("status-update", "Waiting for market logs directory...")
-                .ok();
-            sleep(Duration::from_secs(5)).await;
-            continue;
+    loop {
+        // A restart is how commands ask the watcher to start over with new settings
+        if std::mem::take(&mut restart_requested) {
+            settings = WatcherSettings::load(&app);
+            failed_watches = FailedWatches::default();
+            native_failure = None;
         }
+        let sources = watched_sources(&log_dir, &watched_dirs).await;
+        tracker.clear_pending();
+        let poll_interval = settings.poll_interval;
 
-        // Create directory if it doesn't exist
-        if let Err(e) = std::fs::create_dir_all(&current_dir) {
-            eprintln!("Failed to create log directory: {}", e);
-            sleep(Duration::from_secs(5)).await;
-            continue;
-        }
+        let mut backend = if settings.force_polling || native_failure.is_some() {
+            WatcherBackend::Polling
+        } else {
+            WatcherBackend::Native
+        };
 
         // Create file watcher
         let (tx, rx) = mpsc::channel();
         let (async_tx, mut async_rx) = tokio::sync::mpsc::channel(128);
-        let mut watcher: RecommendedWatcher =
-            Watcher::new(tx, Config::default()).expect("Failed to create watcher");
+        let created = create_watcher(&tx, backend, poll_interval).or_else(|e| {
+            if backend != WatcherBackend::Native {
+                return Err(e);
+            }
+            // No inotify instances left, or no OS support at all
+            native_failure = Some(e.to_string());
+            backend = WatcherBackend::Polling;
+            create_watcher(&tx, backend, poll_interval)
+        });
+        // Only the watcher may keep the channel open, so the forwarding task below
+        // ends together with it
+        drop(tx);
+        let mut watcher = match created {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                eprintln!("Failed to create watcher: {}", e);
+                app.emit("status-update", format!("Watch error: {}", e)).ok();
+                control.record_error(&app, format!("Failed to create watcher: {}", e))
+                    .await;
+                restart_requested = control.sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        // One watcher covers every directory that exists; the others are retried below
+        let mut watching = Vec::new();
+        let mut switch_to_polling = false;
+        for source in &sources {
+            if !source.dir.exists() {
+                failed_watches.clear(&source.dir);
+                continue;
+            }
+            if !failed_watches.is_due(&source.dir, Instant::now()) {
+                continue;
+            }
+            if let Err(e) = watcher.watch(&source.dir, RecursiveMode::NonRecursive) {
+                eprintln!("Failed to watch directory: {}", e);
+                if backend == WatcherBackend::Native {
+                    // E.g. the inotify watch limit; polling has no such limit
+                    native_failure = Some(e.to_string());
+                    switch_to_polling = true;
+                    break;
+                }
+                app.emit("status-update", format!("Watch error: {}", e)).ok();
+                control
+                    .record_error(&app, format!("Failed to watch {}: {}", source.dir.display(), e))
+                    .await;
+                failed_watches.record(&source.dir, Instant::now());
+                continue;
+            }
+            failed_watches.clear(&source.dir);
+            watching.push(source.clone());
+        }
+        if switch_to_polling {
+            continue;
+        }
 
-        if let Err(e) = watcher.watch(&current_dir, RecursiveMode::NonRecursive) {
-            eprintln!("Failed to watch directory: {}", e);
-            app.emit("status-update", format!("Watch error: {}", e)).ok();
-            sleep(Duration::from_secs(5)).await;
+        if watching.is_empty() {
+            let status = control
+                .update(&app, |status| {
+                    status.state = WatcherState::Waiting;
+                    status.backend = None;
+                    status.watched_paths.clear();
+                })
+                .await;
+            app.emit("status-update", status.message()).ok();
+            restart_requested = control.sleep(DIR_CHECK_INTERVAL).await;
             continue;
         }
 
-        app.emit("status-update", "Watching for market logs...")
+        let backend_status = WatcherBackendStatus {
+            backend,
+            poll_interval_secs: (backend == WatcherBackend::Polling)
+                .then_some(poll_interval.as_secs_f64()),
+            fallback_reason: native_failure.clone(),
+        };
+        app.emit("watcher-backend", &backend_status).ok();
+        control
+            .update(&app, |status| {
+                status.state = WatcherState::Watching;
+                status.backend = Some(backend);
+                status.watched_paths = watching.iter().map(|source| source.dir.clone()).collect();
+            })
+            .await;
+
+        let polling_note = match backend {
+            WatcherBackend::Native => String::new(),
+            WatcherBackend::Polling => format!(" (polling every {}s)", poll_interval.as_secs_f64()),
+        };
+        if watching.len() == 1 {
+            app.emit(
+                "status-update",
+                format!("Watching for market logs{}...", polling_note),
+            )
+            .ok();
+        } else {
+            app.emit(
+                "status-update",
+                format!("Watching {} market log folders{}...", watching.len(), polling_note),
+            )
             .ok();
+        }
+
+        // Scan only once the watcher is armed, so nothing exported in between is missed
+        if !control.is_paused().await {
+            if let Some(lookback) = settings.scan_lookback {
+                scan_existing_logs(&app, &watching, lookback, &context, &control, &mut tracker)
+                    .await;
+            }
+        }
 
         // Spawn blocking task to receive from notify channel and forward to async channel
         let async_tx_clone = async_tx.clone();
         tokio::task::spawn_blocking(move || {
-            loop {
-                match rx.recv() {
-                    Ok(event) => {
-                        if async_tx_clone.blocking_send(event).is_err() {
-                            break;
-                        }
-                    }
-                    Err(_) => break,
+            while let Ok(event) = rx.recv() {
+                if async_tx_clone.blocking_send(event).is_err() {
+                    break;
                 }
             }
         });
 
-        // Listen for file changes from async channel
+        // Listen for file changes from async channel, and read exports once they settle
+        let mut settle_poll = tokio::time::interval(SETTLE_POLL_INTERVAL);
+        let mut dir_check = tokio::time::interval(DIR_CHECK_INTERVAL);
         loop {
-            match async_rx.recv().await {
-                Some(Ok(event)) => {
-                    if let EventKind::Create(_) = event.kind {
-                        for path in event.paths {
-                            if path.extension().and_then(|s: &std::ffi::OsStr| s.to_str()) == Some("txt") {
-                                // Wait a bit for file to be fully written
-                                sleep(Duration::from_millis(100)).await;
-
-                                // Try to read and parse the file
-                                if let Ok(content) = std::fs::read_to_string(&path) {
-                                    // Extract item name from filename
-                                    let filename = path
-                                        .file_name()
-                                        .and_then(|n: &std::ffi::OsStr| n.to_str())
-                                        .unwrap_or("");
-                                    let item_name = market_parser::extract_item_name_from_filename(
-                                        filename,
-                                    );
-
-                                    // Get current profile ranges
-                                    let profile = current_profile.read().await;
-                                    let buy_range = profile.buy_range;
-                                    let sell_range = profile.sell_range;
-                                    drop(profile); // Release the lock
-
-                                    // Use profile ranges for filtering orders
-                                    if let Some(mut market_data) =
-                                        market_parser::parse_market_log(&content, buy_range, sell_range)
-                                    {
-                                        let item_name_clone = item_name.clone();
-                                        market_data.item_name = item_name;
-                                        app.emit("market-data", &market_data).ok();
-                                        app.emit("status-update", format!("Processed: {}", item_name_clone))
-                                            .ok();
-                                    }
-                                }
+            tokio::select! {
+                event = async_rx.recv() => match event {
+                    Some(Ok(event)) => {
+                        // EVE may create the file empty and write it afterwards, and a
+                        // rename reports the new name as a modification
+                        if let EventKind::Create(_) | EventKind::Modify(_) = event.kind {
+                            let paths: Vec<PathBuf> = event
+                                .paths
+                                .into_iter()
+                                .filter(|path| is_market_log(path))
+                                .collect();
+                            if paths.is_empty() {
+                                continue;
+                            }
+                            // Not sent as an event, EVE writes an export in many steps
+                            control.status.write().await.last_event_at = Some(Utc::now());
+                            if control.is_paused().await {
+                                continue;
+                            }
+                            for path in paths {
+                                tracker.touch(path);
                             }
                         }
                     }
+                    Some(Err(e)) => {
+                        eprintln!("Watcher error: {}", e);
+                        control.record_error(&app, format!("Watcher error: {}", e)).await;
+                        if backend == WatcherBackend::Native
+                            && matches!(e.kind, notify::ErrorKind::MaxFilesWatch)
+                        {
+                            native_failure = Some(e.to_string());
+                            break;
+                        }
+                    }
+                    None => {
+                        eprintln!("Channel closed");
+                        break;
+                    }
+                },
+                _ = settle_poll.tick(), if tracker.has_pending() => {
+                    // Drop exports that were still settling when the watcher was paused
+                    if control.is_paused().await {
+                        tracker.clear_pending();
+                        continue;
+                    }
+                    for path in tracker.settled_exports() {
+                        let Some(source) = LogSource::of(&watching, &path) else {
+                            continue;
+                        };
+                        process_settled_export(
+                            &app,
+                            &path,
+                            source,
+                            &context,
+                            &control,
+                            &mut tracker,
+                        )
+                        .await;
+                    }
                 }
-                Some(Err(e)) => {
-                    eprintln!("Watcher error: {}", e);
+                // Re-arm when a watched directory was deleted, a missing one appeared or
+                // one that failed to be watched is due to be retried; a watch on a
+                // deleted directory never fires again
+                _ = dir_check.tick() => {
+                    let now = Instant::now();
+                    let changed = sources.iter().any(|source| {
+                        let exists = source.dir.exists();
+                        watching.contains(source) != exists
+                            && (!exists || failed_watches.is_due(&source.dir, now))
+                    });
+                    if changed {
+                        break;
+                    }
                 }
-                None => {
-                    eprintln!("Channel closed");
+                // Dropping the watcher at the end of this iteration stops the old one
+                _ = control.restart.notified() => {
+                    restart_requested = true;
                     break;
                 }
             }
         }
     }
 }
+
+/// Directories that exist but could not be watched, retried with a delay that doubles
+/// after every failure, so the watcher is not re-armed every directory check.
+#[derive(Default)]
+struct FailedWatches {
+    retries: HashMap<PathBuf, WatchRetry>,
+}
+
+struct WatchRetry {
+    delay: Duration,
+    retry_at: Instant,
+}
+
+impl FailedWatches {
+    fn record(&mut self, dir: &Path, now: Instant) {
+        let delay = match self.retries.get(dir) {
+            Some(retry) => (retry.delay * 2).min(MAX_WATCH_RETRY_DELAY),
+            None => DIR_CHECK_INTERVAL,
+        };
+        self.retries.insert(
+            dir.to_path_buf(),
+            WatchRetry {
+                delay,
+                retry_at: now + delay,
+            },
+        );
+    }
+
+    fn clear(&mut self, dir: &Path) {
+        self.retries.remove(dir);
+    }
+
+    fn is_due(&self, dir: &Path, now: Instant) -> bool {
+        self.retries
+            .get(dir)
+            .is_none_or(|retry| now >= retry.retry_at)
+    }
+}
+
+/// Identifies one version of a file, so an export is only processed again once it
+/// has actually been rewritten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileFingerprint {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileFingerprint {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(FileFingerprint {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// An export the watcher has seen change but not processed yet.
+struct PendingExport {
+    fingerprint: Option<FileFingerprint>,
+    changed_at: Instant,
+    attempts: u32,
+}
+
+/// Debounces file events until each export has finished being written, and remembers
+/// which exports were processed so duplicate events are ignored.
+#[derive(Default)]
+struct ExportTracker {
+    pending: HashMap<PathBuf, PendingExport>,
+    processed: HashMap<PathBuf, FileFingerprint>,
+}
+
+impl ExportTracker {
+    fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    fn clear_pending(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Records a change to `path` and restarts its settle time.
+    fn touch(&mut self, path: PathBuf) {
+        let pending = self.pending.entry(path).or_insert(PendingExport {
+            fingerprint: None,
+            changed_at: Instant::now(),
+            attempts: 0,
+        });
+        pending.changed_at = Instant::now();
+    }
+
+    /// Pending exports whose size and modification time have stopped changing. Files
+    /// that were renamed away or were already processed in this version are dropped.
+    fn settled_exports(&mut self) -> Vec<PathBuf> {
+        let now = Instant::now();
+        let processed = &self.processed;
+        let mut settled = Vec::new();
+
+        self.pending.retain(|path, pending| {
+            let Some(fingerprint) = FileFingerprint::of(path) else {
+                return false;
+            };
+            if processed.get(path) == Some(&fingerprint) {
+                return false;
+            }
+
+            if pending.fingerprint != Some(fingerprint) {
+                pending.fingerprint = Some(fingerprint);
+                pending.changed_at = now;
+            } else if fingerprint.len > 0 && now.duration_since(pending.changed_at) >= SETTLE_TIME {
+                settled.push(path.clone());
+            }
+            true
+        });
+        settled
+    }
+
+    /// Waits another settle time before reading `path` again. Returns false once the
+    /// export has used up its attempts and should be processed as it is.
+    fn retry(&mut self, path: &Path) -> bool {
+        let Some(pending) = self.pending.get_mut(path) else {
+            return false;
+        };
+        pending.attempts += 1;
+        pending.changed_at = Instant::now();
+        pending.attempts < MAX_READ_ATTEMPTS
+    }
+
+    /// Stops waiting for `path`; only a new event brings it back.
+    fn give_up(&mut self, path: &Path) {
+        self.pending.remove(path);
+    }
+
+    fn mark_processed(&mut self, path: &Path, fingerprint: FileFingerprint) {
+        self.pending.remove(path);
+        self.processed.insert(path.to_path_buf(), fingerprint);
+        if self.processed.len() > MAX_PROCESSED_EXPORTS {
+            let oldest = self
+                .processed
+                .iter()
+                .min_by_key(|(_, fingerprint)| fingerprint.modified)
+                .map(|(path, _)| path.clone());
+            if let Some(oldest) = oldest {
+                self.processed.remove(&oldest);
+            }
+        }
+    }
+
+    fn is_processed(&self, path: &Path, fingerprint: FileFingerprint) -> bool {
+        self.processed.get(path) == Some(&fingerprint)
+    }
+}
+
+/// Reads a settled export, and puts it back to wait when it still looks half-written.
+async fn process_settled_export(
+    app: &AppHandle,
+    path: &Path,
+    source: &LogSource,
+    context: &WatcherContext,
+    control: &WatcherControl,
+    tracker: &mut ExportTracker,
+) {
+    let before = FileFingerprint::of(path);
+    let loaded = load_market_log(path);
+    let after = FileFingerprint::of(path);
+
+    let Some(fingerprint) = after.filter(|after| before == Some(*after)) else {
+        // Still being written, or gone again; a later event brings it back
+        if !tracker.retry(path) {
+            tracker.give_up(path);
+            if after.is_some() {
+                control
+                    .record_error(app, format!("{} kept changing while being read", path.display()))
+                    .await;
+            }
+        }
+        return;
+    };
+
+    match loaded {
+        Some(loaded) if loaded.is_complete() || !tracker.retry(path) => {
+            tracker.mark_processed(path, fingerprint);
+            let file_name = loaded.report.file_name.clone();
+            match process_loaded_log(app, loaded, source, context, control, true).await {
+                Ok(_) => {
+                    control
+                        .update(app, |status| status.files_processed += 1)
+                        .await;
+                    emit_session(app, context).await;
+                }
+                Err(e) => control.record_error(app, format!("{}: {}", file_name, e)).await,
+            }
+        }
+        Some(_) => {}
+        None => {
+            // Windows refuses reads while EVE holds the file open
+            if !tracker.retry(path) {
+                tracker.mark_processed(path, fingerprint);
+                app.emit("status-update", format!("Failed to read {}", path.display()))
+                    .ok();
+                control
+                    .record_error(app, format!("Failed to read {}", path.display()))
+                    .await;
+            }
+        }
+    }
+}
+
+fn is_market_log(path: &Path) -> bool {
+    path.extension().and_then(|s: &std::ffi::OsStr| s.to_str()) == Some("txt")
+}
+
+/// An export read from disk and parsed, but not yet summarized.
+struct LoadedLog {
+    result: Result<OrderBook, MarketLogError>,
+    report: ParseReport,
+    ends_with_newline: bool,
+}
+
+impl LoadedLog {
+    /// False when the export looks cut off, which usually means EVE was still writing it.
+    fn is_complete(&self) -> bool {
+        self.ends_with_newline
+            && !matches!(
+                self.result,
+                Err(MarketLogError::MissingHeader
+                    | MarketLogError::NoOrders
+                    | MarketLogError::Csv(_))
+            )
+    }
+}
+
+fn load_market_log(path: &Path) -> Option<LoadedLog> {
+    // Try to read and parse the file
+    let bytes = std::fs::read(path).ok()?;
+    let (content, encoding) = market_parser::decode_market_log(&bytes);
+
+    // Region, item name and export time come from the filename
+    let filename = path
+        .file_name()
+        .and_then(|n: &std::ffi::OsStr| n.to_str())
+        .unwrap_or("");
+
+    let (result, mut report) = market_parser::parse_market_log(&content, filename);
+    if encoding != LogEncoding::Utf8 {
+        report.warnings.push(format!("Read as {}", encoding));
+    }
+
+    Some(LoadedLog {
+        result,
+        report,
+        ends_with_newline: content.ends_with('\n'),
+    })
+}
+
+/// Summarizes a parsed export and records it in the session. With `announce` the
+/// result is also shown in the UI; the startup scan only shows the newest export it finds.
+async fn process_loaded_log(
+    app: &AppHandle,
+    loaded: LoadedLog,
+    source: &LogSource,
+    context: &WatcherContext,
+    control: &WatcherControl,
+    announce: bool,
+) -> Result<MarketData, MarketLogError> {
+    let LoadedLog {
+        result,
+        mut report,
+        ..
+    } = loaded;
+
+    // Snapshot the profile so the lock is not held while summarizing
+    let profile = match source_profile(app, source) {
+        Ok(Some(profile)) => profile,
+        Ok(None) => context.current_profile.read().await.clone(),
+        Err(e) => {
+            let error = format!("{}, using the current profile instead", e);
+            control.record_error(app, error.clone()).await;
+            report.warnings.push(error);
+            context.current_profile.read().await.clone()
+        }
+    };
+
+    match result {
+        Ok(order_book) => {
+            let market_data = market_parser::summarize_order_book(
+                &order_book,
+                &profile,
+                &*context.station_groups.read().await,
+            );
+            report.warnings.extend(market_data.warnings.iter().cloned());
+            let market_diff = context
+                .order_book_history
+                .write()
+                .await
+                .record(&order_book, &mut report);
+            Sourced::emit(app, "parse-report", &report, source);
+            *context.last_order_book.write().await = Some(order_book);
+            context
+                .session
+                .write()
+                .await
+                .upsert(report.file_name.clone(), market_data.clone(), source.clone());
+
+            if announce {
+                emit_market_data(app, &market_data, source);
+                if let Some(market_diff) = &market_diff {
+                    Sourced::emit(app, "market-diff", market_diff, source);
+                }
+                app.emit("status-update", format!("Processed: {}", market_data.item_name))
+                    .ok();
+            }
+            Ok(market_data)
+        }
+        Err(e) => {
+            Sourced::emit(app, "parse-report", &report, source);
+            if announce {
+                app.emit("status-update", format!("{}: {}", report.file_name, e))
+                    .ok();
+            }
+            Err(e)
+        }
+    }
+}
+
+/// The profile bound to `source`, or `None` when it has none.
+fn source_profile(app: &AppHandle, source: &LogSource) -> Result<Option<Profile>, String> {
+    let Some(profile_name) = source.profile.as_deref() else {
+        return Ok(None);
+    };
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    Profile::load(&app_data_dir, profile_name)
+        .map(Some)
+        .map_err(|e| {
+            format!(
+                "Failed to load profile '{}' for {}: {}",
+                profile_name,
+                source.dir.display(),
+                e
+            )
+        })
+}
+
+fn emit_market_data(app: &AppHandle, market_data: &MarketData, source: &LogSource) {
+    Sourced::emit(app, "market-data", market_data, source);
+}
+
+async fn emit_session(app: &AppHandle, context: &WatcherContext) {
+    app.emit("session-updated", &context.session.read().await.entries)
+        .ok();
+}
+
+/// Processes the most recent export of every item written to any of `sources` within
+/// `lookback`, oldest first, so exports made while the app was closed show up.
+async fn scan_existing_logs(
+    app: &AppHandle,
+    sources: &[LogSource],
+    lookback: chrono::Duration,
+    context: &WatcherContext,
+    control: &WatcherControl,
+    tracker: &mut ExportTracker,
+) {
+    let cutoff = Utc::now() - lookback;
+
+    // The newest export of each item in each region, per directory like the session
+    let mut latest = HashMap::new();
+    for source in sources {
+        let Ok(entries) = std::fs::read_dir(&source.dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !is_market_log(&path) {
+                continue;
+            }
+            let Some(filename) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let log_file_name = MarketLogFileName::parse(filename);
+
+            let exported_at = log_file_name.exported_at_or_modified(&entry);
+            let Some(exported_at) = exported_at.filter(|exported_at| *exported_at >= cutoff) else {
+                continue;
+            };
+
+            let key = (&source.dir, log_file_name.region, log_file_name.item_name);
+            if latest.get(&key).is_none_or(|(newest, _, _)| exported_at > *newest) {
+                latest.insert(key, (exported_at, path, source));
+            }
+        }
+    }
+    if latest.is_empty() {
+        return;
+    }
+
+    let mut files: Vec<_> = latest.into_values().collect();
+    files.sort_by_key(|(exported_at, _, _)| *exported_at);
+
+    let mut loaded = 0;
+    let mut newest = None;
+    for (_, path, source) in files {
+        // Already processed before the watcher was restarted on this directory
+        let Some(fingerprint) = FileFingerprint::of(&path) else {
+            continue;
+        };
+        if tracker.is_processed(&path, fingerprint) {
+            continue;
+        }
+        let Some(log) = load_market_log(&path) else {
+            continue;
+        };
+        tracker.mark_processed(&path, fingerprint);
+        if let Ok(market_data) = process_loaded_log(app, log, source, context, control, false).await {
+            loaded += 1;
+            newest = Some((market_data, source));
+        }
+    }
+    if loaded > 0 {
+        control
+            .update(app, |status| status.files_processed += loaded)
+            .await;
+    }
+
+    emit_session(app, context).await;
+    if let Some((market_data, source)) = newest {
+        emit_market_data(app, &market_data, source);
+        app.emit("status-update", format!("Loaded {} recent market logs", loaded))
+            .ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A market log in a fresh temporary directory, removed again on drop.
+    struct TempLog {
+        dir: PathBuf,
+        path: PathBuf,
+    }
+
+    impl TempLog {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir()
+                .join(format!("market-toolbox-{}-{}", name, std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let path = dir.join("The Forge-Tritanium-2026.10.18 120000.txt");
+            std::fs::write(&path, "price,volRemaining\n").unwrap();
+            TempLog { dir, path }
+        }
+
+        fn append(&self, content: &str) {
+            let mut existing = std::fs::read_to_string(&self.path).unwrap();
+            existing.push_str(content);
+            std::fs::write(&self.path, existing).unwrap();
+        }
+    }
+
+    impl Drop for TempLog {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.dir).ok();
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn events_through_a_symlinked_directory_find_their_source() {
+        let log = TempLog::new("symlink");
+        let link = log.dir.with_extension("link");
+        std::fs::remove_file(&link).ok();
+        std::os::unix::fs::symlink(&log.dir, &link).unwrap();
+
+        let sources = [LogSource {
+            dir: link.clone(),
+            profile: Some("Alt".to_string()),
+        }];
+        let resolved = log.path.canonicalize().unwrap();
+        assert_eq!(LogSource::of(&sources, &resolved), Some(&sources[0]));
+        assert_eq!(LogSource::of(&sources, &link.join("export.txt")), Some(&sources[0]));
+        assert_eq!(LogSource::of(&sources, Path::new("/elsewhere/export.txt")), None);
+        std::fs::remove_file(&link).ok();
+    }
+
+    fn watcher_settings(poll_interval_secs: f64, scan_lookback_hours: f64) -> WatcherSettings {
+        WatcherSettings::from(&AppSettings {
+            poll_interval_secs,
+            scan_lookback_hours,
+            ..AppSettings::default()
+        })
+    }
+
+    #[test]
+    fn poll_interval_is_clamped() {
+        assert_eq!(watcher_settings(2.0, 0.0).poll_interval, Duration::from_secs(2));
+        assert_eq!(watcher_settings(0.0, 0.0).poll_interval, MIN_POLL_INTERVAL);
+        assert_eq!(watcher_settings(-3.0, 0.0).poll_interval, MIN_POLL_INTERVAL);
+        assert_eq!(watcher_settings(f64::NAN, 0.0).poll_interval, MIN_POLL_INTERVAL);
+        assert_eq!(watcher_settings(1e300, 0.0).poll_interval, MAX_POLL_INTERVAL);
+        assert_eq!(watcher_settings(f64::INFINITY, 0.0).poll_interval, MAX_POLL_INTERVAL);
+    }
+
+    #[test]
+    fn scan_lookback_is_clamped() {
+        assert_eq!(watcher_settings(2.0, 24.0).scan_lookback, Some(chrono::Duration::hours(24)));
+        assert_eq!(watcher_settings(2.0, 0.0).scan_lookback, None);
+        assert_eq!(watcher_settings(2.0, -1.0).scan_lookback, None);
+        assert_eq!(watcher_settings(2.0, f64::NAN).scan_lookback, None);
+        let max = chrono::Duration::from_std(MAX_SCAN_LOOKBACK).unwrap();
+        assert_eq!(watcher_settings(2.0, 1e300).scan_lookback, Some(max));
+        assert_eq!(watcher_settings(2.0, f64::INFINITY).scan_lookback, Some(max));
+    }
+
+    /// Touches `path` and lets the settle time pass without waiting for it.
+    fn settle(tracker: &mut ExportTracker, path: &Path) -> Vec<PathBuf> {
+        tracker.touch(path.to_path_buf());
+        tracker.settled_exports();
+        if let Some(pending) = tracker.pending.get_mut(path) {
+            pending.changed_at -= SETTLE_TIME;
+        }
+        tracker.settled_exports()
+    }
+
+    #[test]
+    fn unchanged_processed_export_is_skipped() {
+        let log = TempLog::new("unchanged");
+        let mut tracker = ExportTracker::default();
+        assert_eq!(settle(&mut tracker, &log.path), vec![log.path.clone()]);
+
+        let fingerprint = FileFingerprint::of(&log.path).unwrap();
+        tracker.mark_processed(&log.path, fingerprint);
+        assert!(tracker.is_processed(&log.path, fingerprint));
+        assert!(settle(&mut tracker, &log.path).is_empty());
+        assert!(!tracker.has_pending());
+    }
+
+    #[test]
+    fn rewritten_export_is_processed_again() {
+        let log = TempLog::new("rewritten");
+        let mut tracker = ExportTracker::default();
+        tracker.mark_processed(&log.path, FileFingerprint::of(&log.path).unwrap());
+
+        log.append("5.5,100\n");
+        let fingerprint = FileFingerprint::of(&log.path).unwrap();
+        assert!(!tracker.is_processed(&log.path, fingerprint));
+        assert_eq!(settle(&mut tracker, &log.path), vec![log.path.clone()]);
+    }
+
+    #[test]
+    fn retries_run_out_after_max_read_attempts() {
+        let path = Path::new("/logs/export.txt");
+        let mut tracker = ExportTracker::default();
+        assert!(!tracker.retry(path));
+
+        tracker.touch(path.to_path_buf());
+        for _ in 1..MAX_READ_ATTEMPTS {
+            assert!(tracker.retry(path));
+        }
+        assert!(!tracker.retry(path));
+
+        tracker.give_up(path);
+        assert!(!tracker.has_pending());
+    }
+
+    #[test]
+    fn processed_exports_forget_the_oldest_first() {
+        let mut tracker = ExportTracker::default();
+        let fingerprint = |secs: u64| FileFingerprint {
+            len: 1,
+            modified: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+        };
+        for i in 0..=MAX_PROCESSED_EXPORTS as u64 {
+            tracker.mark_processed(&PathBuf::from(format!("/logs/{}.txt", i)), fingerprint(i + 1));
+        }
+        assert_eq!(tracker.processed.len(), MAX_PROCESSED_EXPORTS);
+        assert!(!tracker.is_processed(Path::new("/logs/0.txt"), fingerprint(1)));
+        assert!(tracker.is_processed(Path::new("/logs/1.txt"), fingerprint(2)));
+    }
+
+    #[test]
+    fn status_message_follows_the_state() {
+        let mut status = WatcherStatus {
+            state: WatcherState::Waiting,
+            ..WatcherStatus::default()
+        };
+        assert_eq!(status.message(), "Waiting for market logs directory...");
+
+        status.state = WatcherState::Watching;
+        status.watched_paths = vec![PathBuf::from("/logs")];
+        assert_eq!(status.message(), "Watching for market logs...");
+        status.watched_paths.push(PathBuf::from("/alt/logs"));
+        assert_eq!(status.message(), "Watching 2 market log folders...");
+    }
+
+    #[test]
+    fn failed_watch_retry_delay_doubles_up_to_the_maximum() {
+        let dir = Path::new("/logs");
+        let start = Instant::now();
+        let mut failed_watches = FailedWatches::default();
+        assert!(failed_watches.is_due(dir, start));
+
+        failed_watches.record(dir, start);
+        assert!(!failed_watches.is_due(dir, start));
+        assert!(failed_watches.is_due(dir, start + DIR_CHECK_INTERVAL));
+
+        failed_watches.record(dir, start);
+        assert!(!failed_watches.is_due(dir, start + DIR_CHECK_INTERVAL));
+        assert!(failed_watches.is_due(dir, start + DIR_CHECK_INTERVAL * 2));
+
+        for _ in 0..10 {
+            failed_watches.record(dir, start);
+        }
+        assert!(!failed_watches.is_due(dir, start + MAX_WATCH_RETRY_DELAY / 2));
+        assert!(failed_watches.is_due(dir, start + MAX_WATCH_RETRY_DELAY));
+    }
+
+    #[test]
+    fn cleared_or_other_directories_are_due() {
+        let start = Instant::now();
+        let mut failed_watches = FailedWatches::default();
+        failed_watches.record(Path::new("/logs"), start);
+        assert!(failed_watches.is_due(Path::new("/alt/logs"), start));
+
+        failed_watches.clear(Path::new("/logs"));
+        assert!(failed_watches.is_due(Path::new("/logs"), start));
+    }
+}
@@ -1,15 +1,25 @@
 mod commands;
 mod file_watcher;
-mod market_parser;
-mod profile;
-mod settings;
+mod log_discovery;
+mod market_diff;
+pub mod market_parser;
+pub mod pricing;
+pub mod profile;
+mod session;
+pub mod settings;
+pub mod station_groups;
 
 use std::path::PathBuf;
 use std::sync::Arc;
 use tauri::Manager;
 use tokio::sync::RwLock;
 use crate::settings::AppSettings;
+use crate::file_watcher::WatcherControl;
 use crate::profile::Profile;
+use crate::market_diff::OrderBookHistory;
+use crate::market_parser::OrderBook;
+use crate::session::MarketSession;
+use crate::station_groups::StationGroups;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -19,20 +29,36 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_opener::init())
         .setup(|app| {
-            // Get default log directory
-            let log_dir = get_default_log_dir();
-            let log_dir_arc = Arc::new(RwLock::new(log_dir.clone()));
-
-            // Initialize current profile state with default profile
             let profiles_dir = app
                 .path()
                 .app_data_dir()
                 .expect("Failed to get app data directory");
+            let settings = AppSettings::load(&profiles_dir).ok();
+
+            // Use the log directory picked in the app, or the client's default
+            let log_dir = settings
+                .as_ref()
+                .and_then(|settings| settings.log_dir.clone())
+                .unwrap_or_else(get_default_log_dir);
+            let log_dir_arc = Arc::new(RwLock::new(log_dir));
+            let watched_dirs = settings
+                .as_ref()
+                .map(|settings| settings.watched_dirs.clone())
+                .unwrap_or_default();
+            let watched_dirs_arc = Arc::new(RwLock::new(watched_dirs));
+
+            // Initialize current profile state with default profile
             let default_profile = Profile::default("Default".to_string());
             let current_profile_arc = Arc::new(RwLock::new(default_profile));
+            let last_order_book_arc: Arc<RwLock<Option<OrderBook>>> = Arc::new(RwLock::new(None));
+            let station_groups = StationGroups::load(&profiles_dir).unwrap_or_else(|_| StationGroups::default());
+            let station_groups_arc = Arc::new(RwLock::new(station_groups));
+            let order_book_history_arc = Arc::new(RwLock::new(OrderBookHistory::default()));
+            let session_arc = Arc::new(RwLock::new(MarketSession::default()));
+            let watcher_control = WatcherControl::default();
             
             // Load the selected profile from settings if available
-            if let Ok(settings) = AppSettings::load(&profiles_dir) {
+            if let Some(settings) = &settings {
                 if let Ok(profile) = Profile::load(&profiles_dir, &settings.selected_profile) {
                     *current_profile_arc.blocking_write() = profile;
                 }
@@ -41,13 +67,33 @@ pub fn run() {
             // Initialize file watcher
             let app_handle = app.handle().clone();
             let log_dir_for_watcher = log_dir_arc.clone();
-            let profile_for_watcher = current_profile_arc.clone();
+            let watched_dirs_for_watcher = watched_dirs_arc.clone();
+            let watcher_context = file_watcher::WatcherContext {
+                current_profile: current_profile_arc.clone(),
+                last_order_book: last_order_book_arc.clone(),
+                station_groups: station_groups_arc.clone(),
+                order_book_history: order_book_history_arc.clone(),
+                session: session_arc.clone(),
+            };
+            let control_for_watcher = watcher_control.clone();
             tauri::async_runtime::spawn(async move {
-                file_watcher::watch_market_logs(app_handle, log_dir_for_watcher, profile_for_watcher).await;
+                file_watcher::watch_market_logs(
+                    app_handle,
+                    log_dir_for_watcher,
+                    watched_dirs_for_watcher,
+                    watcher_context,
+                    control_for_watcher,
+                )
+                .await;
             });
 
             app.manage(log_dir_arc);
+            app.manage(watched_dirs_arc);
             app.manage(current_profile_arc);
+            app.manage(last_order_book_arc);
+            app.manage(station_groups_arc);
+            app.manage(session_arc);
+            app.manage(watcher_control);
 
             // Initialize profiles directory
             let profiles_dir = app
@@ -83,8 +129,26 @@ pub fn run() {
             commands::save_profile,
             commands::delete_profile,
             commands::select_log_path,
+            commands::set_log_path,
+            commands::discover_log_dirs,
             commands::load_settings,
             commands::save_settings,
+            commands::get_order_book,
+            commands::get_depth_prices,
+            commands::check_buy_reachability,
+            commands::get_station_breakdown,
+            commands::find_arbitrage,
+            commands::list_station_groups,
+            commands::save_station_group,
+            commands::delete_station_group,
+            commands::calculate_profit,
+            commands::get_session,
+            commands::list_watched_dirs,
+            commands::save_watched_dir,
+            commands::delete_watched_dir,
+            commands::pause_watcher,
+            commands::resume_watcher,
+            commands::watcher_status,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -143,3 +207,41 @@ fn get_default_log_dir() -> PathBuf {
         path
     }
 }
+
+/// Must match `identifier` in tauri.conf.json, which names the app data directory.
+const APP_IDENTIFIER: &str = "com.markettoolbox.app";
+
+/// Where Tauri's `app_data_dir()` points, for tools that read profiles and settings
+/// without running the app.
+pub fn default_app_data_dir() -> PathBuf {
+    #[cfg(windows)]
+    {
+        let mut path = PathBuf::from(std::env::var("APPDATA").unwrap_or_default());
+        path.push(APP_IDENTIFIER);
+        path
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut path = PathBuf::from(std::env::var("HOME").unwrap_or_default());
+        path.push("Library");
+        path.push("Application Support");
+        path.push(APP_IDENTIFIER);
+        path
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let mut path = match std::env::var("XDG_DATA_HOME") {
+            Ok(data_home) if !data_home.is_empty() => PathBuf::from(data_home),
+            _ => {
+                let mut path = PathBuf::from(std::env::var("HOME").unwrap_or_default());
+                path.push(".local");
+                path.push("share");
+                path
+            }
+        };
+        path.push(APP_IDENTIFIER);
+        path
+    }
+}
@@ -0,0 +1,382 @@
+use crate::market_parser::MarketLogFileName;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// EVE Online's Steam app id, which names its Proton prefix under `compatdata`.
+const EVE_STEAM_APP_ID: &str = "8500";
+
+/// Exports newer than this count as recent activity when ranking candidates.
+const RECENT_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogDirOrigin {
+    Windows,
+    SteamProton,
+    WinePrefix,
+    Native,
+}
+
+/// A market logs folder found on this machine, with how much it has been used.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogDirCandidate {
+    pub path: PathBuf,
+    pub origin: LogDirOrigin,
+    pub market_log_count: usize,
+    pub recent_market_log_count: usize,
+    pub last_exported_at: Option<DateTime<Utc>>,
+}
+
+/// Finds existing market logs folders of every EVE install on this machine, the most
+/// recently used first.
+pub fn discover_log_dirs() -> Vec<LogDirCandidate> {
+    let mut candidates: Vec<LogDirCandidate> = Vec::new();
+    for (path, origin) in candidate_dirs() {
+        let Some(path) = find_marketlogs_dir(&path) else {
+            continue;
+        };
+        // Steam's own symlinks lead to the same prefix more than once
+        let path = fs::canonicalize(&path).unwrap_or(path);
+        if candidates.iter().any(|candidate| candidate.path == path) {
+            continue;
+        }
+        candidates.push(inspect(path, origin));
+    }
+
+    rank(&mut candidates);
+    candidates
+}
+
+/// Most recently used first, then the busiest.
+fn rank(candidates: &mut [LogDirCandidate]) {
+    candidates.sort_by(|a, b| {
+        b.last_exported_at
+            .cmp(&a.last_exported_at)
+            .then(b.recent_market_log_count.cmp(&a.recent_market_log_count))
+            .then(b.market_log_count.cmp(&a.market_log_count))
+    });
+}
+
+/// `EVE/logs` folders where a client might write, whether or not they exist.
+#[cfg(windows)]
+fn candidate_dirs() -> Vec<(PathBuf, LogDirOrigin)> {
+    let home = PathBuf::from(std::env::var("USERPROFILE").unwrap_or_default());
+    let mut documents_dirs = vec![home.join("Documents")];
+    // Documents moved by OneDrive folder backup
+    if let Ok(one_drive) = std::env::var("OneDrive") {
+        documents_dirs.push(PathBuf::from(one_drive).join("Documents"));
+    }
+
+    documents_dirs
+        .into_iter()
+        .map(|documents| (documents.join("EVE").join("logs"), LogDirOrigin::Windows))
+        .collect()
+}
+
+/// `EVE/logs` folders where a client might write, whether or not they exist.
+#[cfg(unix)]
+fn candidate_dirs() -> Vec<(PathBuf, LogDirOrigin)> {
+    let home = PathBuf::from(std::env::var("HOME").unwrap_or_default());
+    let mut candidates = vec![
+        (home.join(".local/share/EVE/logs"), LogDirOrigin::Native),
+        (home.join("Documents/EVE/logs"), LogDirOrigin::Native),
+    ];
+
+    for library in steam_libraries(&home) {
+        let prefix = library
+            .join("steamapps/compatdata")
+            .join(EVE_STEAM_APP_ID)
+            .join("pfx");
+        for documents in wine_documents_dirs(&prefix) {
+            candidates.push((documents.join("EVE/logs"), LogDirOrigin::SteamProton));
+        }
+    }
+
+    for prefix in wine_prefixes(&home) {
+        for documents in wine_documents_dirs(&prefix) {
+            candidates.push((documents.join("EVE/logs"), LogDirOrigin::WinePrefix));
+        }
+    }
+
+    candidates
+}
+
+/// Steam installs (native, Flatpak and Snap) plus the extra libraries each one lists
+/// in `libraryfolders.vdf`.
+#[cfg(unix)]
+fn steam_libraries(home: &Path) -> Vec<PathBuf> {
+    let mut libraries: Vec<PathBuf> = [
+        ".steam/steam",
+        ".steam/root",
+        ".local/share/Steam",
+        ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+        "snap/steam/common/.local/share/Steam",
+    ]
+    .iter()
+    .map(|install| home.join(install))
+    .filter(|install| install.is_dir())
+    .collect();
+
+    let mut extra_libraries = Vec::new();
+    for install in &libraries {
+        let Ok(content) = fs::read_to_string(install.join("steamapps/libraryfolders.vdf")) else {
+            continue;
+        };
+        extra_libraries.extend(library_folder_paths(&content));
+    }
+    libraries.extend(extra_libraries);
+    libraries
+}
+
+/// The `"path"` values of a `libraryfolders.vdf` file.
+#[cfg(unix)]
+fn library_folder_paths(content: &str) -> Vec<PathBuf> {
+    content
+        .lines()
+        .filter_map(|line| match &vdf_strings(line)[..] {
+            [key, path] if key == "path" => Some(PathBuf::from(path)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The quoted strings on one line of a VDF file, with `\\` and `\"` unescaped.
+#[cfg(unix)]
+fn vdf_strings(line: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut chars = line.chars();
+    while chars.any(|c| c == '"') {
+        let mut string = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => string.extend(chars.next()),
+                c => string.push(c),
+            }
+        }
+        strings.push(string);
+    }
+    strings
+}
+
+/// Wine prefixes from `WINEPREFIX`, the default one, and the usual Lutris and Bottles
+/// locations.
+#[cfg(unix)]
+fn wine_prefixes(home: &Path) -> Vec<PathBuf> {
+    let mut prefixes = Vec::new();
+    if let Ok(prefix) = std::env::var("WINEPREFIX") {
+        prefixes.push(PathBuf::from(prefix));
+    }
+    prefixes.push(home.join(".wine"));
+
+    // Lutris installs each game into its own prefix under ~/Games by default
+    for parent in [
+        home.join("Games"),
+        home.join(".local/share/lutris/prefixes"),
+        home.join(".local/share/bottles/bottles"),
+        home.join(".var/app/com.usebottles.bottles/data/bottles/bottles"),
+    ] {
+        let Ok(entries) = fs::read_dir(parent) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.join("drive_c").is_dir() {
+                prefixes.push(path);
+            }
+        }
+    }
+    prefixes
+}
+
+/// Every user's Documents folder inside a Wine prefix.
+#[cfg(unix)]
+fn wine_documents_dirs(prefix: &Path) -> Vec<PathBuf> {
+    let Ok(users) = fs::read_dir(prefix.join("drive_c/users")) else {
+        return Vec::new();
+    };
+
+    let mut documents_dirs = Vec::new();
+    for user in users.flatten() {
+        // Older Wine versions still use the XP name
+        for documents in ["Documents", "My Documents"] {
+            let path = user.path().join(documents);
+            if path.is_dir() {
+                documents_dirs.push(path);
+            }
+        }
+    }
+    documents_dirs
+}
+
+/// The market logs folder inside an `EVE/logs` folder. The client names it
+/// `Marketlogs`, but case matters on Linux and older installs differ.
+fn find_marketlogs_dir(logs_dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(logs_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.is_dir()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.eq_ignore_ascii_case("marketlogs"))
+        })
+}
+
+fn inspect(path: PathBuf, origin: LogDirOrigin) -> LogDirCandidate {
+    let recent_cutoff = Utc::now() - Duration::days(RECENT_DAYS);
+    let mut candidate = LogDirCandidate {
+        path,
+        origin,
+        market_log_count: 0,
+        recent_market_log_count: 0,
+        last_exported_at: None,
+    };
+
+    let Ok(entries) = fs::read_dir(&candidate.path) else {
+        return candidate;
+    };
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            continue;
+        };
+        if !file_name.ends_with(".txt") {
+            continue;
+        }
+
+        let exported_at = MarketLogFileName::parse(file_name).exported_at_or_modified(&entry);
+
+        candidate.market_log_count += 1;
+        if exported_at.is_some_and(|exported_at| exported_at >= recent_cutoff) {
+            candidate.recent_market_log_count += 1;
+        }
+        candidate.last_exported_at = candidate.last_exported_at.max(exported_at);
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    const LIBRARY_FOLDERS: &str = r#""libraryfolders"
+{
+	"0"
+	{
+		"path"		"/home/pilot/.local/share/Steam"
+		"label"		""
+		"apps"
+		{
+			"8500"		"51538121467"
+		}
+	}
+	"1"
+	{
+		"path"		"/mnt/games/Steam Library"
+		"label"		"Games"
+	}
+}
+"#;
+
+    #[cfg(unix)]
+    #[test]
+    fn library_folders_lists_every_path() {
+        assert_eq!(
+            library_folder_paths(LIBRARY_FOLDERS),
+            vec![
+                PathBuf::from("/home/pilot/.local/share/Steam"),
+                PathBuf::from("/mnt/games/Steam Library"),
+            ]
+        );
+        assert!(library_folder_paths("").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn vdf_strings_are_unescaped() {
+        assert_eq!(vdf_strings(r#"	"path"		"D:\\SteamLibrary""#), vec!["path", "D:\\SteamLibrary"]);
+        assert_eq!(vdf_strings(r#""a\"b"  "c""#), vec!["a\"b", "c"]);
+        assert_eq!(vdf_strings(r#""label"		"""#), vec!["label", ""]);
+        assert!(vdf_strings("{").is_empty());
+    }
+
+    /// A fresh temporary directory, removed again on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir()
+                .join(format!("market-toolbox-discovery-{}-{}", name, std::process::id()));
+            fs::remove_dir_all(&dir).ok();
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    #[test]
+    fn marketlogs_dir_is_found_in_any_case() {
+        let logs = TempDir::new("case");
+        assert_eq!(find_marketlogs_dir(&logs.0), None);
+
+        fs::write(logs.0.join("marketlogs"), "").unwrap();
+        assert_eq!(find_marketlogs_dir(&logs.0), None);
+        fs::create_dir(logs.0.join("Chatlogs")).unwrap();
+        fs::create_dir(logs.0.join("MarketLogs")).unwrap();
+        assert_eq!(find_marketlogs_dir(&logs.0), Some(logs.0.join("MarketLogs")));
+        assert_eq!(find_marketlogs_dir(&logs.0.join("missing")), None);
+    }
+
+    #[test]
+    fn inspect_counts_exports_by_file_name_time() {
+        let dir = TempDir::new("inspect");
+        let recent = (Utc::now() - Duration::hours(1)).format("%Y.%m.%d %H%M%S");
+        fs::write(dir.0.join(format!("The Forge-Tritanium-{}.txt", recent)), "").unwrap();
+        fs::write(dir.0.join("The Forge-Pyerite-2020.01.01 120000.txt"), "").unwrap();
+        fs::write(dir.0.join("notes.md"), "").unwrap();
+
+        let candidate = inspect(dir.0.clone(), LogDirOrigin::Native);
+        assert_eq!(candidate.market_log_count, 2);
+        assert_eq!(candidate.recent_market_log_count, 1);
+        assert!(candidate.last_exported_at.unwrap() > Utc::now() - Duration::hours(2));
+    }
+
+    fn candidate(name: &str, days_ago: Option<i64>, recent: usize, all: usize) -> LogDirCandidate {
+        LogDirCandidate {
+            path: PathBuf::from(name),
+            origin: LogDirOrigin::Native,
+            market_log_count: all,
+            recent_market_log_count: recent,
+            last_exported_at: days_ago.map(|days| {
+                DateTime::parse_from_rfc3339("2026-10-18T12:00:00Z").unwrap().to_utc()
+                    - Duration::days(days)
+            }),
+        }
+    }
+
+    #[test]
+    fn candidates_rank_most_recently_used_first() {
+        let mut candidates = vec![
+            candidate("unused", None, 0, 0),
+            candidate("old", Some(30), 0, 500),
+            candidate("quiet", Some(1), 2, 10),
+            candidate("busy", Some(1), 40, 40),
+            candidate("latest", Some(0), 1, 1),
+        ];
+        rank(&mut candidates);
+        let order: Vec<&Path> = candidates.iter().map(|c| c.path.as_path()).collect();
+        assert_eq!(order, ["latest", "busy", "quiet", "old", "unused"].map(Path::new));
+    }
+}
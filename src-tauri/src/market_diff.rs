@@ -0,0 +1,443 @@
+use crate::market_parser::{OrderBook, OrderRow, ParseReport};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OrderChangeKind {
+    New,
+    /// Gone from the newer export: filled, cancelled or expired.
+    Vanished,
+    PriceChanged,
+    VolumeDecreased,
+}
+
+/// One order that differs between two exports, matched by `orderID`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderChange {
+    pub order_id: i64,
+    pub kind: OrderChangeKind,
+    pub bid: bool,
+    pub station_id: i64,
+    pub previous_price: Option<f64>,
+    pub price: Option<f64>,
+    pub previous_volume: Option<f64>,
+    pub volume: Option<f64>,
+    /// Units that certainly changed hands: the drop in remaining volume of an order
+    /// present in both exports. A repriced order can have traded as well.
+    pub traded_volume: f64,
+    /// A vanished order whose duration ran out before the newer export.
+    pub expired: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PriceMoveCause {
+    /// A freshly placed order beat the previous best price.
+    NewOrder,
+    /// An existing order was updated to the new best price.
+    Repriced,
+    /// The previous best order vanished or moved away, exposing the next one.
+    BestOrderRemoved,
+}
+
+/// How the best price on one side of the book moved, and which order moved it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceMove {
+    pub previous_price: f64,
+    pub price: f64,
+    pub cause: PriceMoveCause,
+    /// The order now holding the best price.
+    pub order_id: i64,
+    pub station_id: i64,
+}
+
+/// What changed on one side of the book between two exports.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SideDiff {
+    pub new_orders: usize,
+    pub vanished_orders: usize,
+    pub price_changes: usize,
+    pub volume_decreases: usize,
+    /// Units traded out of orders still listed, see `OrderChange::traded_volume`.
+    pub traded_volume: f64,
+    /// Remaining units of vanished orders that had not expired; an upper bound on
+    /// what was filled since cancelled orders vanish too.
+    pub vanished_volume: f64,
+    pub price_move: Option<PriceMove>,
+}
+
+/// The difference between two exports of the same item in the same region.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketDiff {
+    pub item_name: String,
+    pub type_id: i32,
+    pub region: Option<String>,
+    pub previous_exported_at: Option<DateTime<Utc>>,
+    pub exported_at: Option<DateTime<Utc>>,
+    pub elapsed_minutes: Option<f64>,
+    pub sell: SideDiff,
+    pub buy: SideDiff,
+    pub changes: Vec<OrderChange>,
+}
+
+/// Compares two exports of the same item order by order.
+pub fn diff_order_books(previous: &OrderBook, current: &OrderBook) -> MarketDiff {
+    let previous_orders: HashMap<i64, &OrderRow> = previous
+        .orders
+        .iter()
+        .map(|order| (order.order_id, order))
+        .collect();
+    let current_order_ids: HashSet<i64> = current.orders.iter().map(|order| order.order_id).collect();
+
+    let mut sell = SideDiff::default();
+    let mut buy = SideDiff::default();
+    let mut changes = Vec::new();
+    let mut repriced = HashSet::new();
+
+    for order in &current.orders {
+        let side = if order.bid { &mut buy } else { &mut sell };
+        let Some(previous_order) = previous_orders.get(&order.order_id) else {
+            side.new_orders += 1;
+            changes.push(OrderChange {
+                order_id: order.order_id,
+                kind: OrderChangeKind::New,
+                bid: order.bid,
+                station_id: order.station_id,
+                previous_price: None,
+                price: Some(order.price),
+                previous_volume: None,
+                volume: Some(order.vol_remaining),
+                traded_volume: 0.0,
+                expired: false,
+            });
+            continue;
+        };
+
+        let traded_volume = (previous_order.vol_remaining - order.vol_remaining).max(0.0);
+        let kind = if order.price != previous_order.price {
+            side.price_changes += 1;
+            repriced.insert(order.order_id);
+            OrderChangeKind::PriceChanged
+        } else if traded_volume > 0.0 {
+            side.volume_decreases += 1;
+            OrderChangeKind::VolumeDecreased
+        } else {
+            continue;
+        };
+        side.traded_volume += traded_volume;
+
+        changes.push(OrderChange {
+            order_id: order.order_id,
+            kind,
+            bid: order.bid,
+            station_id: order.station_id,
+            previous_price: Some(previous_order.price),
+            price: Some(order.price),
+            previous_volume: Some(previous_order.vol_remaining),
+            volume: Some(order.vol_remaining),
+            traded_volume,
+            expired: false,
+        });
+    }
+
+    for order in &previous.orders {
+        if current_order_ids.contains(&order.order_id) {
+            continue;
+        }
+        let side = if order.bid { &mut buy } else { &mut sell };
+        let expired = match (order.expires_at, current.exported_at) {
+            (Some(expires_at), Some(exported_at)) => expires_at <= exported_at,
+            _ => false,
+        };
+        side.vanished_orders += 1;
+        if !expired {
+            side.vanished_volume += order.vol_remaining;
+        }
+
+        changes.push(OrderChange {
+            order_id: order.order_id,
+            kind: OrderChangeKind::Vanished,
+            bid: order.bid,
+            station_id: order.station_id,
+            previous_price: Some(order.price),
+            price: None,
+            previous_volume: Some(order.vol_remaining),
+            volume: None,
+            traded_volume: 0.0,
+            expired,
+        });
+    }
+
+    sell.price_move = find_price_move(previous, current, false, &previous_orders, &repriced);
+    buy.price_move = find_price_move(previous, current, true, &previous_orders, &repriced);
+
+    MarketDiff {
+        item_name: current.item_name.clone(),
+        type_id: current.type_id,
+        region: current.region.clone(),
+        previous_exported_at: previous.exported_at,
+        exported_at: current.exported_at,
+        elapsed_minutes: match (previous.exported_at, current.exported_at) {
+            (Some(previous), Some(current)) => {
+                Some((current - previous).num_seconds() as f64 / 60.0)
+            }
+            _ => None,
+        },
+        sell,
+        buy,
+        changes,
+    }
+}
+
+/// Lowest sell or highest buy order; ties go to the oldest order, which the market
+/// fills first.
+fn best_order(book: &OrderBook, is_buy: bool) -> Option<&OrderRow> {
+    book.orders
+        .iter()
+        .filter(|order| order.bid == is_buy)
+        .min_by(|a, b| {
+            let by_price = if is_buy {
+                b.price.total_cmp(&a.price)
+            } else {
+                a.price.total_cmp(&b.price)
+            };
+            by_price.then(a.issue_date.cmp(&b.issue_date))
+        })
+}
+
+fn find_price_move(
+    previous: &OrderBook,
+    current: &OrderBook,
+    is_buy: bool,
+    previous_orders: &HashMap<i64, &OrderRow>,
+    repriced: &HashSet<i64>,
+) -> Option<PriceMove> {
+    let previous_best = best_order(previous, is_buy)?;
+    let best = best_order(current, is_buy)?;
+    if best.price == previous_best.price {
+        return None;
+    }
+
+    let cause = if !previous_orders.contains_key(&best.order_id) {
+        PriceMoveCause::NewOrder
+    } else if repriced.contains(&best.order_id) {
+        PriceMoveCause::Repriced
+    } else {
+        PriceMoveCause::BestOrderRemoved
+    };
+
+    Some(PriceMove {
+        previous_price: previous_best.price,
+        price: best.price,
+        cause,
+        order_id: best.order_id,
+        station_id: best.station_id,
+    })
+}
+
+/// The most recent export of every item seen this session, to diff the next one against.
+#[derive(Default)]
+pub struct OrderBookHistory {
+    books: HashMap<(Option<String>, i32), OrderBook>,
+}
+
+impl OrderBookHistory {
+    /// Stores `book` as the latest export of its item and returns the diff against
+    /// the previous one. An export older than the stored one is ignored, and one
+    /// without order IDs is left out with a warning in `report`.
+    pub fn record(&mut self, book: &OrderBook, report: &mut ParseReport) -> Option<MarketDiff> {
+        // Every order reads as ID 0 when the export has no orderID column
+        if book.orders.iter().all(|order| order.order_id == 0) {
+            report
+                .warnings
+                .push("No order IDs to match, not comparing with the previous export".to_string());
+            return None;
+        }
+
+        let key = (book.region.clone(), book.type_id);
+        if let Some(previous) = self.books.get(&key) {
+            if let (Some(previous_at), Some(exported_at)) = (previous.exported_at, book.exported_at) {
+                if exported_at <= previous_at {
+                    return None;
+                }
+            }
+        }
+
+        let previous = self.books.insert(key, book.clone());
+        previous.map(|previous| diff_order_books(&previous, book))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 18, hour, 0, 0).unwrap()
+    }
+
+    fn order(order_id: i64, bid: bool, price: f64, vol_remaining: f64) -> OrderRow {
+        OrderRow {
+            price,
+            vol_remaining,
+            type_id: 34,
+            range: -1,
+            order_id,
+            vol_entered: 1000.0,
+            min_volume: 1.0,
+            bid,
+            issue_date: Some(at(0)),
+            duration: 90,
+            station_id: 60003760,
+            region_id: 10000002,
+            solar_system_id: 30000142,
+            jumps: 0,
+            expires_at: Some(at(0) + chrono::Duration::days(90)),
+            age_days: None,
+            expires_in_days: None,
+        }
+    }
+
+    fn book(exported_at: DateTime<Utc>, orders: Vec<OrderRow>) -> OrderBook {
+        OrderBook {
+            item_name: "Tritanium".to_string(),
+            type_id: 34,
+            region: Some("The Forge".to_string()),
+            exported_at: Some(exported_at),
+            orders,
+        }
+    }
+
+    fn change(diff: &MarketDiff, order_id: i64) -> &OrderChange {
+        diff.changes.iter().find(|change| change.order_id == order_id).unwrap()
+    }
+
+    #[test]
+    fn orders_are_matched_by_id() {
+        let expired = OrderRow {
+            expires_at: Some(at(1)),
+            ..order(4, false, 5.8, 50.0)
+        };
+        let previous = book(
+            at(10),
+            vec![
+                order(1, false, 5.5, 100.0),
+                order(2, false, 5.6, 100.0),
+                order(3, true, 4.0, 200.0),
+                expired,
+            ],
+        );
+        let current = book(
+            at(11),
+            vec![
+                order(1, false, 5.5, 60.0),
+                order(3, true, 4.1, 150.0),
+                order(5, false, 5.7, 10.0),
+            ],
+        );
+        let diff = diff_order_books(&previous, &current);
+
+        assert_eq!(diff.elapsed_minutes, Some(60.0));
+        assert_eq!(diff.changes.len(), 5);
+        assert_eq!(change(&diff, 1).kind, OrderChangeKind::VolumeDecreased);
+        assert_eq!(change(&diff, 1).traded_volume, 40.0);
+        assert_eq!(change(&diff, 2).kind, OrderChangeKind::Vanished);
+        assert!(!change(&diff, 2).expired);
+        assert_eq!(change(&diff, 3).kind, OrderChangeKind::PriceChanged);
+        assert_eq!(change(&diff, 3).traded_volume, 50.0);
+        assert_eq!(change(&diff, 4).kind, OrderChangeKind::Vanished);
+        assert!(change(&diff, 4).expired);
+        assert_eq!(change(&diff, 5).kind, OrderChangeKind::New);
+
+        assert_eq!(diff.sell.new_orders, 1);
+        assert_eq!(diff.sell.vanished_orders, 2);
+        assert_eq!(diff.sell.volume_decreases, 1);
+        assert_eq!(diff.sell.traded_volume, 40.0);
+        // The expired order could not have been filled
+        assert_eq!(diff.sell.vanished_volume, 100.0);
+        assert_eq!(diff.buy.price_changes, 1);
+        assert_eq!(diff.buy.traded_volume, 50.0);
+    }
+
+    #[test]
+    fn price_moves_name_their_cause() {
+        let previous = book(
+            at(10),
+            vec![
+                order(1, false, 5.5, 100.0),
+                order(2, false, 5.6, 100.0),
+                order(3, true, 4.0, 1.0),
+            ],
+        );
+
+        let undercut = book(
+            at(11),
+            vec![
+                order(1, false, 5.5, 100.0),
+                order(2, false, 5.4, 100.0),
+                order(3, true, 4.0, 1.0),
+            ],
+        );
+        let price_move = diff_order_books(&previous, &undercut).sell.price_move.unwrap();
+        assert_eq!(price_move.cause, PriceMoveCause::Repriced);
+        assert_eq!(price_move.previous_price, 5.5);
+        assert_eq!(price_move.price, 5.4);
+        assert_eq!(price_move.order_id, 2);
+
+        let new_order = book(
+            at(11),
+            vec![
+                order(1, false, 5.5, 100.0),
+                order(9, false, 5.49, 1.0),
+                order(3, true, 4.0, 1.0),
+            ],
+        );
+        let price_move = diff_order_books(&previous, &new_order).sell.price_move.unwrap();
+        assert_eq!(price_move.cause, PriceMoveCause::NewOrder);
+
+        let filled = book(at(11), vec![order(2, false, 5.6, 100.0), order(3, true, 4.0, 1.0)]);
+        let diff = diff_order_books(&previous, &filled);
+        assert_eq!(diff.sell.price_move.unwrap().cause, PriceMoveCause::BestOrderRemoved);
+        assert!(diff.buy.price_move.is_none());
+    }
+
+    #[test]
+    fn history_diffs_against_the_previous_export() {
+        let mut history = OrderBookHistory::default();
+        let mut report = ParseReport::default();
+
+        let first = book(at(10), vec![order(1, false, 5.5, 100.0)]);
+        assert!(history.record(&first, &mut report).is_none());
+        let diff = history.record(&book(at(11), vec![order(1, false, 5.5, 90.0)]), &mut report);
+        assert_eq!(diff.unwrap().sell.traded_volume, 10.0);
+        // Older than the stored export
+        let older = book(at(9), vec![order(1, false, 5.5, 50.0)]);
+        assert!(history.record(&older, &mut report).is_none());
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn exports_without_order_ids_are_not_diffed() {
+        let mut history = OrderBookHistory::default();
+        let mut report = ParseReport::default();
+        history.record(&book(at(10), vec![order(1, false, 5.5, 100.0)]), &mut report);
+
+        let without_ids = book(
+            at(11),
+            vec![order(0, false, 5.5, 90.0), order(0, false, 5.7, 10.0)],
+        );
+        assert!(history.record(&without_ids, &mut report).is_none());
+        assert_eq!(report.warnings.len(), 1);
+
+        // The export with IDs is still the one to compare against
+        let diff = history.record(&book(at(12), vec![order(1, false, 5.5, 80.0)]), &mut report);
+        assert_eq!(diff.unwrap().sell.traded_volume, 20.0);
+    }
+}
@@ -1,31 +1,246 @@
+use crate::pricing::{self, CalculatedData};
+use crate::profile::Profile;
+use crate::station_groups::StationGroups;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::BufRead;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MarketData {
     pub item_name: String,
     pub type_id: i32,
+    pub region: Option<String>,
+    pub exported_at: Option<DateTime<Utc>>,
     pub sell_price: f64,
     pub buy_price: f64,
+    /// Highest legal price below `sell_price` under EVE's tick-size rules.
+    pub suggested_sell_price: f64,
+    /// Lowest legal price above `buy_price` under EVE's tick-size rules.
+    pub suggested_buy_price: f64,
     pub sell_order_count: usize,
     pub buy_order_count: usize,
     pub sell_price_95_ci: f64,
     pub buy_price_95_ci: f64,
+    /// `sell_price_95_ci` rounded down onto the tick grid, or `suggested_sell_price`
+    /// without an interval.
+    pub suggested_sell_price_95_ci: f64,
+    /// `buy_price_95_ci` rounded up onto the tick grid, or `suggested_buy_price`
+    /// without an interval.
+    pub suggested_buy_price_95_ci: f64,
     pub sell_total_quantity: f64,
     pub buy_total_quantity: f64,
     pub sell_total_isk_value: f64,
     pub buy_total_isk_value: f64,
+    pub depth: DepthPrices,
+    pub sell_price_statistics: Option<PriceStatistics>,
+    pub buy_price_statistics: Option<PriceStatistics>,
+    pub price_estimator: PriceEstimator,
+    pub sell_price_estimate: f64,
+    pub buy_price_estimate: f64,
+    pub buy_reachability: Option<BuyReachabilitySummary>,
+    pub min_volume_blocked_count: usize,
+    pub min_volume_blocked_best_price: f64,
+    /// Orders in range left out because they are older than the profile's `max_order_age_days`.
+    pub old_order_count: usize,
+    /// Minutes between the export time in the filename and now.
+    pub export_age_minutes: Option<f64>,
+    pub is_stale: bool,
+    /// Stations with orders used for the prices above, i.e. in the profile's ranges.
+    pub stations: Vec<StationSummary>,
+    /// Fees and profit at `suggested_sell_price` and `suggested_buy_price` under the
+    /// profile used.
+    pub calculated: CalculatedData,
+    /// Profile settings that could not be applied as intended, for the parse report.
+    pub warnings: Vec<String>,
 }
 
-const HUB_IDS: [f64; 5] = [60003760.0, 60004588.0, 60008494.0, 60011866.0, 60005686.0];
-const HUB_IDS_SET: [f64; 5] = HUB_IDS;
+/// Buying from a sell order and selling the same units into a buy order for a profit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArbitrageOpportunity {
+    pub buy_from_order_id: i64,
+    pub buy_from_station_id: i64,
+    pub buy_price: f64,
+    pub sell_to_order_id: i64,
+    pub sell_to_station_id: i64,
+    pub sell_price: f64,
+    /// Where to sell: the purchase station when the buy order's range covers it,
+    /// otherwise the buy order's own station.
+    pub sell_at_station_id: i64,
+    pub quantity: f64,
+    /// Hauling distance bounds; equal when the distance is known exactly.
+    pub min_jumps: i32,
+    pub max_jumps: i32,
+    /// Buy-side broker fee on the purchase, at the profile's rate.
+    pub broker_fee: f64,
+    pub sales_tax: f64,
+    pub net_profit: f64,
+    pub net_profit_per_unit: f64,
+}
+
+/// Best prices and depth at one station of a regional export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StationSummary {
+    pub station_id: i64,
+    pub solar_system_id: i64,
+    pub jumps: i32,
+    pub best_bid: f64,
+    pub best_ask: f64,
+    pub buy_order_count: usize,
+    pub sell_order_count: usize,
+    pub buy_total_quantity: f64,
+    pub sell_total_quantity: f64,
+    pub buy_total_isk_value: f64,
+    pub sell_total_isk_value: f64,
+}
+
+/// What to do with buy orders whose minimum volume exceeds the units we intend to sell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MinVolumeHandling {
+    /// Leave them out of prices and totals.
+    Exclude,
+    /// Keep them, but still report how many there are.
+    Flag,
+}
+
+pub fn default_min_volume_handling() -> MinVolumeHandling {
+    MinVolumeHandling::Exclude
+}
+
+pub fn default_units_to_sell() -> f64 {
+    1.0
+}
+
+pub fn default_stale_after_minutes() -> f64 {
+    15.0
+}
+
+/// The statistic used for `sell_price_estimate`/`buy_price_estimate`.
+///
+/// Bounds and quantiles are always taken from the conservative side of the book:
+/// the low end for sell orders (to avoid undercuts) and the high end for buy orders.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum PriceEstimator {
+    /// Mean -/+ 1.96 standard errors, the original 95% CI.
+    NormalCi,
+    /// Mean -/+ Student's t critical value, better for a handful of orders.
+    TDistributionCi,
+    Median,
+    /// Percentile (0-100) for sell orders, mirrored (100 - p) for buy orders.
+    Percentile { percentile: f64 },
+    /// Mean after dropping `trim_percent` (0-50) of orders from each end.
+    TrimmedMean { trim_percent: f64 },
+    /// Best price after dropping orders further than `threshold` scaled MADs from the median.
+    MedianAbsoluteDeviation { threshold: f64 },
+    /// Quantile (0-1) weighted by remaining volume, mirrored (1 - q) for buy orders.
+    VolumeWeightedQuantile { quantile: f64 },
+}
+
+pub fn default_price_estimator() -> PriceEstimator {
+    PriceEstimator::NormalCi
+}
+
+/// Summary statistics over the prices on one side of the book.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceStatistics {
+    pub count: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub median: f64,
+    pub p10: f64,
+    pub p25: f64,
+    pub p75: f64,
+    pub p90: f64,
+    pub trimmed_mean_10: f64,
+    pub median_absolute_deviation: f64,
+    pub volume_weighted_median: f64,
+    pub t_ci_lower: f64,
+    pub t_ci_upper: f64,
+}
 
+/// Whether a buy order's range covers a given selling station.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Reachability {
+    Reachable,
+    /// The export only has jumps from the player, so the distance between two other
+    /// systems can only be bounded, not measured.
+    Uncertain,
+    Unreachable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuyReachabilitySummary {
+    pub station_id: i64,
+    pub station_found: bool,
+    pub reachable: usize,
+    pub uncertain: usize,
+    pub unreachable: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderReachability {
+    pub order_id: i64,
+    pub price: f64,
+    pub station_id: i64,
+    pub range: i32,
+    pub reachability: Reachability,
+}
+
+/// How much of the book a depth-weighted price should walk through.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "camelCase")]
+pub enum DepthTarget {
+    /// A fixed number of units.
+    Quantity(f64),
+    /// A percentage (0-100) of the total volume on that side of the book.
+    DepthPercent(f64),
+}
+
+pub fn default_depth_target() -> DepthTarget {
+    DepthTarget::DepthPercent(5.0)
+}
+
+/// The volume-weighted average price of filling a target quantity against the book.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepthPrice {
+    pub target_quantity: f64,
+    pub filled_quantity: f64,
+    pub average_price: f64,
+    pub worst_price: f64,
+    pub total_isk_value: f64,
+    pub fully_filled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepthPrices {
+    pub target: DepthTarget,
+    /// What buying the target from the cheapest sell orders costs per unit.
+    pub sell: Option<DepthPrice>,
+    /// What selling the target into the highest buy orders earns per unit.
+    pub buy: Option<DepthPrice>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OrderRange {
     Hub = 0,
     System = 1,
     OneJump = 2,
     TwoJump = 3,
     Region = 4,
+    /// Buy orders whose own range reaches the profile's selling station.
+    Reachable = 5,
 }
 
 impl From<u8> for OrderRange {
@@ -35,236 +250,2466 @@ impl From<u8> for OrderRange {
             1 => OrderRange::System,
             2 => OrderRange::OneJump,
             3 => OrderRange::TwoJump,
+            5 => OrderRange::Reachable,
             _ => OrderRange::Region,
         }
     }
 }
 
+/// A single order as exported by the EVE client, one field per CSV column.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct OrderRow {
-    price: f64,
-    quantity: f64,
-    is_buy_order: bool,
-    location_id: f64,
-    jumps: i32,
-    type_id: i32,
+    pub price: f64,
+    pub vol_remaining: f64,
+    pub type_id: i32,
+    pub range: i32,
+    pub order_id: i64,
+    pub vol_entered: f64,
+    pub min_volume: f64,
+    pub bid: bool,
+    pub issue_date: Option<DateTime<Utc>>,
+    pub duration: i32,
+    pub station_id: i64,
+    pub region_id: i64,
+    pub solar_system_id: i64,
+    pub jumps: i32,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Days since the order was issued, as of the export time in the filename (or the
+    /// time of parsing when the file was renamed).
+    pub age_days: Option<f64>,
+    /// Days until the order expires, as of the export time.
+    pub expires_in_days: Option<f64>,
 }
 
-fn parse_csv_row(row: &csv::StringRecord) -> Option<OrderRow> {
-    if row.len() < 14 {
-        return None;
+impl OrderRow {
+    pub fn refresh_age(&mut self, reference: DateTime<Utc>) {
+        let days = |duration: chrono::TimeDelta| duration.num_seconds() as f64 / 86_400.0;
+        self.age_days = self.issue_date.map(|issued| days(reference - issued));
+        self.expires_in_days = self.expires_at.map(|expires| days(expires - reference));
+    }
+}
+
+/// Every order parsed from one market log export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBook {
+    pub item_name: String,
+    pub type_id: i32,
+    pub region: Option<String>,
+    pub exported_at: Option<DateTime<Utc>>,
+    pub orders: Vec<OrderRow>,
+}
+
+/// Errors that make a whole market log unusable, as opposed to single bad rows.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarketLogError {
+    MissingHeader,
+    MissingColumns(Vec<String>),
+    Csv(String),
+    NoOrders,
+}
+
+impl std::fmt::Display for MarketLogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MarketLogError::MissingHeader => write!(f, "Market log has no header line"),
+            MarketLogError::MissingColumns(columns) => write!(
+                f,
+                "Unsupported market log layout, missing columns: {}",
+                columns.join(", ")
+            ),
+            MarketLogError::Csv(e) => write!(f, "Failed to read market log: {}", e),
+            MarketLogError::NoOrders => write!(f, "No orders found in market log"),
+        }
+    }
+}
+
+impl std::error::Error for MarketLogError {}
+
+/// Why a single CSV row was left out of the order book.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum RowRejectReason {
+    WrongColumnCount { expected: usize, found: usize },
+    BadPrice { value: String },
+    BadTypeId { value: String },
+    BadStationId { value: String },
+    MissingJumps,
+    BadJumps { value: String },
+    Unreadable { message: String },
+}
+
+impl std::fmt::Display for RowRejectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RowRejectReason::WrongColumnCount { expected, found } => {
+                write!(f, "expected {} columns, found {}", expected, found)
+            }
+            RowRejectReason::BadPrice { value } => write!(f, "invalid price '{}'", value),
+            RowRejectReason::BadTypeId { value } => write!(f, "invalid typeID '{}'", value),
+            RowRejectReason::BadStationId { value } => write!(f, "invalid stationID '{}'", value),
+            RowRejectReason::MissingJumps => write!(f, "missing jumps"),
+            RowRejectReason::BadJumps { value } => write!(f, "invalid jumps '{}'", value),
+            RowRejectReason::Unreadable { message } => write!(f, "unreadable row: {}", message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RowRejection {
+    pub line: u64,
+    pub reason: RowRejectReason,
+    pub message: String,
+}
+
+/// Only the first rejections are kept so a garbage file cannot balloon the report.
+const MAX_REPORTED_REJECTIONS: usize = 100;
+
+/// What happened while parsing one export, for display next to the results.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseReport {
+    pub file_name: String,
+    pub total_rows: usize,
+    pub parsed_rows: usize,
+    pub skipped_rows: usize,
+    pub rejections: Vec<RowRejection>,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl ParseReport {
+    fn reject(&mut self, line: u64, reason: RowRejectReason) {
+        self.skipped_rows += 1;
+        if self.rejections.len() < MAX_REPORTED_REJECTIONS {
+            let message = reason.to_string();
+            self.rejections.push(RowRejection { line, reason, message });
+        }
     }
+}
+
+/// Column positions resolved from the header line of an export.
+///
+/// Required columns are the ones an order cannot be understood without; the rest
+/// fall back to neutral values when CCP drops them from the export.
+struct ColumnMap {
+    price: usize,
+    vol_remaining: usize,
+    type_id: usize,
+    range: Option<usize>,
+    order_id: Option<usize>,
+    vol_entered: Option<usize>,
+    min_volume: Option<usize>,
+    bid: usize,
+    issue_date: Option<usize>,
+    duration: Option<usize>,
+    station_id: usize,
+    region_id: Option<usize>,
+    solar_system_id: Option<usize>,
+    jumps: usize,
+    width: usize,
+    unknown_columns: Vec<String>,
+    missing_optional_columns: Vec<String>,
+}
 
-    // Skip header row if present (check if first column looks like a header)
-    if let Some(first_col) = row.get(0) {
-        let trimmed = first_col.trim();
-        if trimmed.eq_ignore_ascii_case("price") || 
-           trimmed.chars().next().map(|c| c.is_alphabetic()).unwrap_or(false) {
-            return None;
+const KNOWN_COLUMNS: [&str; 14] = [
+    "price",
+    "volRemaining",
+    "typeID",
+    "range",
+    "orderID",
+    "volEntered",
+    "minVolume",
+    "bid",
+    "issueDate",
+    "duration",
+    "stationID",
+    "regionID",
+    "solarSystemID",
+    "jumps",
+];
+
+impl ColumnMap {
+    fn from_header(header: &csv::StringRecord) -> Result<Self, MarketLogError> {
+        let find = |name: &str| {
+            header
+                .iter()
+                .position(|column| column_name(column).eq_ignore_ascii_case(name))
+        };
+
+        if find("price").is_none() && find("typeID").is_none() {
+            return Err(MarketLogError::MissingHeader);
+        }
+
+        let mut missing = Vec::new();
+        let mut require = |name: &str| {
+            find(name).unwrap_or_else(|| {
+                missing.push(name.to_string());
+                0
+            })
+        };
+
+        let price = require("price");
+        let vol_remaining = require("volRemaining");
+        let type_id = require("typeID");
+        let bid = require("bid");
+        let station_id = require("stationID");
+        let jumps = require("jumps");
+
+        if !missing.is_empty() {
+            return Err(MarketLogError::MissingColumns(missing));
         }
+
+        let width = KNOWN_COLUMNS
+            .iter()
+            .filter_map(|name| find(name))
+            .max()
+            .map_or(0, |index| index + 1);
+
+        let missing_optional_columns = KNOWN_COLUMNS
+            .iter()
+            .filter(|name| find(name).is_none())
+            .map(|name| name.to_string())
+            .collect();
+
+        // EVE ends every line with a comma, so an empty trailing column is expected
+        let unknown_columns = header
+            .iter()
+            .map(column_name)
+            .filter(|column| {
+                !column.is_empty()
+                    && !KNOWN_COLUMNS
+                        .iter()
+                        .any(|known| known.eq_ignore_ascii_case(column))
+            })
+            .map(str::to_string)
+            .collect();
+
+        Ok(ColumnMap {
+            price,
+            vol_remaining,
+            type_id,
+            range: find("range"),
+            order_id: find("orderID"),
+            vol_entered: find("volEntered"),
+            min_volume: find("minVolume"),
+            bid,
+            issue_date: find("issueDate"),
+            duration: find("duration"),
+            station_id,
+            region_id: find("regionID"),
+            solar_system_id: find("solarSystemID"),
+            jumps,
+            width,
+            unknown_columns,
+            missing_optional_columns,
+        })
+    }
+}
+
+/// A header cell without surrounding whitespace or a byte order mark left over from
+/// a decoder that kept it.
+fn column_name(column: &str) -> &str {
+    column.trim_start_matches('\u{feff}').trim()
+}
+
+fn parse_csv_row(
+    row: &csv::StringRecord,
+    columns: &ColumnMap,
+    expected_columns: usize,
+    decimal: DecimalSeparator,
+) -> Result<OrderRow, RowRejectReason> {
+    if row.len() < columns.width {
+        return Err(RowRejectReason::WrongColumnCount {
+            expected: expected_columns,
+            found: row.len(),
+        });
     }
 
-    let price = row.get(0)?.parse::<f64>().ok()?;
-    let quantity = row.get(1)?.parse::<f64>().ok().unwrap_or(0.0);
-    let is_buy_order = row.get(7)?.eq_ignore_ascii_case("true");
-    let location_id = row.get(10)?.parse::<f64>().ok()?;
-    let jumps = row.get(13)?.parse::<i32>().ok()?;
-    let type_id = row.get(2)?.parse::<i32>().ok()?;
+    let cell = |index: usize| row.get(index).map(str::trim).unwrap_or_default();
+    let optional = |index: Option<usize>| index.map(cell);
+    let number = |value: &str| parse_number(value, decimal);
 
-    Some(OrderRow {
+    let price = number(cell(columns.price))
+        .filter(|price| price.is_finite() && *price >= 0.0)
+        .ok_or_else(|| RowRejectReason::BadPrice {
+            value: cell(columns.price).to_string(),
+        })?;
+    let vol_remaining = number(cell(columns.vol_remaining)).unwrap_or(0.0);
+    let type_id = cell(columns.type_id)
+        .parse::<i32>()
+        .map_err(|_| RowRejectReason::BadTypeId {
+            value: cell(columns.type_id).to_string(),
+        })?;
+    let range = optional(columns.range).and_then(|v| v.parse::<i32>().ok()).unwrap_or(0);
+    let order_id = optional(columns.order_id).and_then(|v| v.parse::<i64>().ok()).unwrap_or(0);
+    let vol_entered = optional(columns.vol_entered).and_then(number).unwrap_or(0.0);
+    let min_volume = optional(columns.min_volume).and_then(number).unwrap_or(1.0);
+    let bid = cell(columns.bid).eq_ignore_ascii_case("true");
+    let issue_date = optional(columns.issue_date).and_then(parse_issue_date);
+    let duration = optional(columns.duration).and_then(|v| v.parse::<i32>().ok()).unwrap_or(0);
+    let expires_at = issue_date.map(|issued| issued + chrono::TimeDelta::days(duration.into()));
+    let station_id = cell(columns.station_id)
+        .parse::<i64>()
+        .map_err(|_| RowRejectReason::BadStationId {
+            value: cell(columns.station_id).to_string(),
+        })?;
+    let region_id = optional(columns.region_id).and_then(|v| v.parse::<i64>().ok()).unwrap_or(0);
+    let solar_system_id = optional(columns.solar_system_id).and_then(|v| v.parse::<i64>().ok()).unwrap_or(0);
+    let jumps = match cell(columns.jumps) {
+        "" => return Err(RowRejectReason::MissingJumps),
+        value => value.parse::<i32>().map_err(|_| RowRejectReason::BadJumps {
+            value: value.to_string(),
+        })?,
+    };
+
+    Ok(OrderRow {
         price,
-        quantity,
-        is_buy_order,
-        location_id,
-        jumps,
+        vol_remaining,
         type_id,
+        range,
+        order_id,
+        vol_entered,
+        min_volume,
+        bid,
+        issue_date,
+        duration,
+        station_id,
+        region_id,
+        solar_system_id,
+        jumps,
+        expires_at,
+        age_days: None,
+        expires_in_days: None,
     })
 }
 
-fn filter_orders_by_range(
-    orders: &[OrderRow],
-    range: OrderRange,
-    is_buy: bool,
-) -> Vec<&OrderRow> {
-    orders
-        .iter()
-        .filter(|order| {
-            if order.is_buy_order != is_buy {
-                return false;
-            }
+/// Which character separates the decimals in one export. The other of `.` and `,`
+/// groups thousands, along with spaces (including non-breaking ones) and apostrophes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DecimalSeparator {
+    Dot,
+    Comma,
+}
 
-            match range {
-                OrderRange::Hub => order.jumps == 0 && HUB_IDS_SET.iter().any(|&id| id == order.location_id),
-                OrderRange::System => order.jumps == 0,
-                OrderRange::OneJump => order.jumps < 2,
-                OrderRange::TwoJump => order.jumps < 3,
-                OrderRange::Region => true,
+fn is_digit_grouping(c: char) -> bool {
+    matches!(c, ' ' | '\u{a0}' | '\u{202f}' | '\'')
+}
+
+/// Parses a number as written by the client locale the export was sniffed as, e.g.
+/// `1234.56`, `1,234.56`, `1 234,56` or `1.234,56`.
+fn parse_number(value: &str, decimal: DecimalSeparator) -> Option<f64> {
+    let (decimal, grouping) = match decimal {
+        DecimalSeparator::Dot => {
+            if let Ok(number) = value.parse::<f64>() {
+                return Some(number);
             }
+            ('.', ',')
+        }
+        DecimalSeparator::Comma => (',', '.'),
+    };
+
+    let digits: String = value
+        .chars()
+        .filter(|&c| c != grouping && !is_digit_grouping(c))
+        .map(|c| if c == decimal { '.' } else { c })
+        .collect();
+    digits.parse::<f64>().ok()
+}
+
+/// Decides the decimal separator for a whole export from its first lines, so a value
+/// like `1,500` means the same in every row. Values that could go either way, with
+/// three digits after their only separator, are skipped; without any clue the
+/// English client's dot is assumed.
+fn sniff_decimal_separator(start: &[u8], delimiter: u8) -> DecimalSeparator {
+    // A decimal comma would split the field in a comma separated file
+    if delimiter == b',' {
+        return DecimalSeparator::Dot;
+    }
+
+    let sample = String::from_utf8_lossy(start);
+    let fields = sample
+        .lines()
+        .skip(1)
+        .flat_map(|line| line.split(char::from(delimiter)))
+        .map(str::trim)
+        .filter(|field| {
+            field.starts_with(|c: char| c.is_ascii_digit())
+                && field
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || c == '.' || c == ',' || is_digit_grouping(c))
+        });
+
+    for field in fields {
+        let decimals_after = |index: usize| field.len() - index - 1;
+        match (field.rfind('.'), field.rfind(',')) {
+            (Some(dot), Some(comma)) if comma > dot => return DecimalSeparator::Comma,
+            (Some(_), Some(_)) => return DecimalSeparator::Dot,
+            (None, Some(comma)) if decimals_after(comma) != 3 => return DecimalSeparator::Comma,
+            (Some(dot), None) if decimals_after(dot) != 3 => return DecimalSeparator::Dot,
+            _ => {}
+        }
+    }
+    DecimalSeparator::Dot
+}
+
+fn parse_issue_date(value: &str) -> Option<DateTime<Utc>> {
+    parse_issue_date_fast(value).or_else(|| {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f")
+            .ok()
+            .map(|naive| naive.and_utc())
+    })
+}
+
+/// Reads the fixed `YYYY-MM-DD HH:MM:SS.fff` layout the client always writes without
+/// going through chrono's format parser, which dominates parse time on large exports.
+fn parse_issue_date_fast(value: &str) -> Option<DateTime<Utc>> {
+    let bytes = value.as_bytes();
+    if bytes.len() < 19
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || bytes[10] != b' '
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return None;
+    }
+    let number = |range: std::ops::Range<usize>| -> Option<u32> {
+        bytes[range].iter().try_fold(0u32, |acc, &b| {
+            b.is_ascii_digit().then(|| acc * 10 + u32::from(b - b'0'))
         })
-        .collect()
+    };
+
+    let millis = match &bytes[19..] {
+        [] => 0,
+        [b'.', digits @ ..] if (1..=3).contains(&digits.len()) => {
+            let value = number(20..bytes.len())?;
+            value * 10u32.pow(3 - digits.len() as u32)
+        }
+        _ => return None,
+    };
+
+    NaiveDate::from_ymd_opt(number(0..4)? as i32, number(5..7)?, number(8..10)?)?
+        .and_hms_milli_opt(number(11..13)?, number(14..16)?, number(17..19)?, millis)
+        .map(|naive| naive.and_utc())
 }
 
-fn calculate_mean(prices: &[f64]) -> f64 {
-    if prices.is_empty() {
-        return 0.0;
+fn is_in_range(order: &OrderRow, range: OrderRange, hub_station_ids: &[i64]) -> bool {
+    match range {
+        OrderRange::Hub => order.jumps == 0 && hub_station_ids.contains(&order.station_id),
+        OrderRange::System => order.jumps == 0,
+        OrderRange::OneJump => order.jumps < 2,
+        OrderRange::TwoJump => order.jumps < 3,
+        OrderRange::Region => true,
+        // Needs the selling station's location, see `resolve_pending_buy_orders`
+        OrderRange::Reachable => false,
     }
-    prices.iter().sum::<f64>() / prices.len() as f64
 }
 
-fn calculate_std_dev(prices: &[f64], mean: f64) -> f64 {
-    if prices.len() < 2 {
-        return 0.0;
+/// Buy order `range` values with a special meaning; anything in between is a jump
+/// count, 0 meaning the order's own solar system.
+const RANGE_STATION: i32 = -1;
+const RANGE_REGION: i32 = 32767;
+
+/// What `solarSystemID` reads as when the export has no such column.
+const UNKNOWN_SOLAR_SYSTEM: i64 = 0;
+
+/// Where a station sits relative to the player, learned from any order listed there.
+struct StationLocation {
+    solar_system_id: i64,
+    jumps: i32,
+}
+
+impl StationLocation {
+    fn of(order: &OrderRow) -> Self {
+        StationLocation {
+            solar_system_id: order.solar_system_id,
+            jumps: order.jumps,
+        }
+    }
+
+    /// Whether both stations are in one system, `None` when the export cannot tell.
+    fn same_system_as(&self, other: &StationLocation) -> Option<bool> {
+        (self.solar_system_id != UNKNOWN_SOLAR_SYSTEM && other.solar_system_id != UNKNOWN_SOLAR_SYSTEM)
+            .then_some(self.solar_system_id == other.solar_system_id)
+    }
+
+    /// Bounds on the jumps between two stations. Both distances are measured from the
+    /// player, which bounds the distance between the two systems; it is exact when one
+    /// of them is the player's system.
+    fn jumps_to(&self, other: &StationLocation) -> (i32, i32) {
+        let difference = (self.jumps - other.jumps).abs();
+        let sum = self.jumps + other.jumps;
+        match self.same_system_as(other) {
+            Some(true) => (0, 0),
+            Some(false) => (difference.max(1), sum),
+            // Two stations the same distance away may or may not share a system
+            None => (difference, sum),
+        }
     }
-    let variance = prices
+}
+
+fn locate_station(orders: &[OrderRow], station_id: i64) -> Option<StationLocation> {
+    orders
         .iter()
-        .map(|&price| (price - mean).powi(2))
-        .sum::<f64>() / (prices.len() - 1) as f64;
-    variance.sqrt()
+        .find(|order| order.station_id == station_id)
+        .map(StationLocation::of)
 }
 
-fn calculate_95_ci_lower(prices: &[f64]) -> f64 {
-    if prices.is_empty() {
-        return -1.0;
+fn buy_order_reachability(
+    order: &OrderRow,
+    station_id: i64,
+    location: Option<&StationLocation>,
+) -> Reachability {
+    if order.station_id == station_id || order.range >= RANGE_REGION {
+        return Reachability::Reachable;
     }
-    if prices.len() == 1 {
-        return prices[0];
+    if order.range <= RANGE_STATION {
+        return Reachability::Unreachable;
+    }
+
+    let Some(location) = location else {
+        return Reachability::Uncertain;
+    };
+
+    let (lower, upper) = StationLocation::of(order).jumps_to(location);
+    if upper <= order.range {
+        Reachability::Reachable
+    } else if lower > order.range {
+        Reachability::Unreachable
+    } else {
+        Reachability::Uncertain
     }
-    let mean = calculate_mean(prices);
-    let std_dev = calculate_std_dev(prices, mean);
-    let n = prices.len() as f64;
-    let standard_error = std_dev / n.sqrt();
-    // Z-score for 95% confidence is 1.96
-    let z_score = 1.96;
-    mean - (z_score * standard_error)
 }
 
-fn calculate_95_ci_upper(prices: &[f64]) -> f64 {
-    if prices.is_empty() {
-        return -1.0;
+/// Classifies every buy order in the book by whether it can be filled from `station_id`.
+pub fn check_buy_reachability(book: &OrderBook, station_id: i64) -> Vec<OrderReachability> {
+    let location = locate_station(&book.orders, station_id);
+    book.orders
+        .iter()
+        .filter(|order| order.bid)
+        .map(|order| OrderReachability {
+            order_id: order.order_id,
+            price: order.price,
+            station_id: order.station_id,
+            range: order.range,
+            reachability: buy_order_reachability(order, station_id, location.as_ref()),
+        })
+        .collect()
+}
+
+fn is_order_too_old(order: &OrderRow, max_age_days: f64) -> bool {
+    order.age_days.is_some_and(|age| age > max_age_days)
+}
+
+/// One order's price and remaining volume, all the statistics need to keep per order.
+#[derive(Debug, Clone, Copy)]
+struct PriceLevel {
+    price: f64,
+    volume: f64,
+}
+
+/// Running totals for one side of the book, fed one order at a time.
+#[derive(Default)]
+struct SideAccumulator {
+    total_quantity: f64,
+    total_isk_value: f64,
+    // Welford's online mean and sum of squared deviations
+    mean: f64,
+    m2: f64,
+    levels: Vec<PriceLevel>,
+}
+
+impl SideAccumulator {
+    fn push(&mut self, order: &OrderRow) {
+        self.levels.push(PriceLevel {
+            price: order.price,
+            volume: order.vol_remaining,
+        });
+        self.total_quantity += order.vol_remaining;
+        self.total_isk_value += order.price * order.vol_remaining;
+
+        let delta = order.price - self.mean;
+        self.mean += delta / self.levels.len() as f64;
+        self.m2 += delta * (order.price - self.mean);
     }
-    if prices.len() == 1 {
-        return prices[0];
+
+    fn finish(mut self) -> SideStats {
+        self.levels.sort_by(|a, b| a.price.total_cmp(&b.price));
+        let count = self.levels.len();
+        SideStats {
+            mean: self.mean,
+            std_dev: if count < 2 {
+                0.0
+            } else {
+                (self.m2 / (count - 1) as f64).sqrt()
+            },
+            total_quantity: self.total_quantity,
+            total_isk_value: self.total_isk_value,
+            prices: self.levels.iter().map(|level| level.price).collect(),
+            levels: self.levels,
+        }
     }
-    let mean = calculate_mean(prices);
-    let std_dev = calculate_std_dev(prices, mean);
-    let n = prices.len() as f64;
-    let standard_error = std_dev / n.sqrt();
-    // Z-score for 95% confidence is 1.96
-    let z_score = 1.96;
-    mean + (z_score * standard_error)
 }
 
-pub fn parse_market_log(csv_content: &str, buy_range: u8, sell_range: u8) -> Option<MarketData> {
-    let mut reader = csv::ReaderBuilder::new()
-        .has_headers(false)
-        .flexible(true)
-        .from_reader(csv_content.as_bytes());
+/// Final statistics for one side of the book; `levels` and `prices` are sorted ascending.
+struct SideStats {
+    mean: f64,
+    std_dev: f64,
+    total_quantity: f64,
+    total_isk_value: f64,
+    levels: Vec<PriceLevel>,
+    prices: Vec<f64>,
+}
 
-    let mut orders = Vec::new();
-    let mut type_id = -1;
-    let mut item_name = String::new();
-    let mut total_rows = 0;
-    let mut parsed_rows = 0;
-
-    for result in reader.records() {
-        total_rows += 1;
-        if let Ok(record) = result {
-            if let Some(order) = parse_csv_row(&record) {
-                if type_id == -1 {
-                    type_id = order.type_id;
+impl SideStats {
+    fn count(&self) -> usize {
+        self.prices.len()
+    }
+
+    fn lowest_price(&self) -> f64 {
+        self.prices.first().copied().unwrap_or(-1.0)
+    }
+
+    fn highest_price(&self) -> f64 {
+        self.prices.last().copied().unwrap_or(-1.0)
+    }
+}
+
+/// Everything `MarketData` reports, built in a single pass over the orders. Buy orders
+/// that depend on the selling station's location are held back until it has been seen;
+/// orders from an `OrderBook` are held by reference, only streamed ones are kept owned.
+struct MarketAccumulator<'a> {
+    profile: &'a Profile,
+    sell_range: OrderRange,
+    buy_range: OrderRange,
+    sell_hub_ids: &'a [i64],
+    buy_hub_ids: &'a [i64],
+    sell: SideAccumulator,
+    buy: SideAccumulator,
+    sell_station_location: Option<StationLocation>,
+    pending_buy_orders: Vec<Cow<'a, OrderRow>>,
+    buy_reachability: Option<BuyReachabilitySummary>,
+    min_volume_blocked_count: usize,
+    min_volume_blocked_best_price: f64,
+    old_order_count: usize,
+    stations: HashMap<i64, StationSummary>,
+    warnings: Vec<String>,
+}
+
+impl<'a> MarketAccumulator<'a> {
+    fn new(profile: &'a Profile, station_groups: &'a StationGroups) -> Self {
+        let sell_range = OrderRange::from(profile.sell_range);
+        let buy_range = OrderRange::from(profile.buy_range);
+        let mut warnings = Vec::new();
+        let mut hub_ids = |range: OrderRange, group: &str, side: &str| {
+            let ids = station_groups.station_ids(group);
+            if range == OrderRange::Hub && ids.is_none() {
+                warnings.push(format!(
+                    "Station group '{}' does not exist, no {} orders are at a hub",
+                    group, side
+                ));
+            }
+            ids.unwrap_or_default()
+        };
+        let sell_hub_ids = hub_ids(sell_range, &profile.sell_hub_group, "sell");
+        let buy_hub_ids = hub_ids(buy_range, &profile.buy_hub_group, "buy");
+        if buy_range == OrderRange::Reachable && profile.sell_station_id.is_none() {
+            warnings.push(
+                "The reachable buy range needs a selling station, no buy orders are counted"
+                    .to_string(),
+            );
+        }
+
+        MarketAccumulator {
+            profile,
+            sell_range,
+            buy_range,
+            sell_hub_ids,
+            buy_hub_ids,
+            sell: SideAccumulator::default(),
+            buy: SideAccumulator::default(),
+            sell_station_location: None,
+            pending_buy_orders: Vec::new(),
+            buy_reachability: profile.sell_station_id.map(|station_id| BuyReachabilitySummary {
+                station_id,
+                station_found: false,
+                reachable: 0,
+                uncertain: 0,
+                unreachable: 0,
+            }),
+            min_volume_blocked_count: 0,
+            min_volume_blocked_best_price: -1.0,
+            old_order_count: 0,
+            stations: HashMap::new(),
+            warnings,
+        }
+    }
+
+    fn push(&mut self, order: Cow<'a, OrderRow>) {
+        if let Some(station_id) = self.profile.sell_station_id {
+            if self.sell_station_location.is_none() && order.station_id == station_id {
+                self.sell_station_location = Some(StationLocation::of(&order));
+                self.resolve_pending_buy_orders();
+            }
+            if order.bid {
+                if self.sell_station_location.is_some() {
+                    self.push_located_buy(&order, station_id);
+                } else {
+                    // Reachability is only known once the selling station has been located
+                    self.pending_buy_orders.push(order);
                 }
-                orders.push(order);
-                parsed_rows += 1;
+                return;
+            }
+        }
+
+        let order = order.as_ref();
+        if order.bid {
+            if is_in_range(order, self.buy_range, self.buy_hub_ids) && !self.is_too_old(order) {
+                self.push_buy(order);
             }
+        } else if is_in_range(order, self.sell_range, self.sell_hub_ids) && !self.is_too_old(order) {
+            self.sell.push(order);
+            add_to_station(&mut self.stations, order);
         }
     }
 
-    eprintln!("CSV parsing: {} total rows, {} parsed orders", total_rows, parsed_rows);
+    /// Checked last, so `old_order_count` only counts orders that would otherwise be used.
+    fn is_too_old(&mut self, order: &OrderRow) -> bool {
+        let too_old = self
+            .profile
+            .max_order_age_days
+            .is_some_and(|max_age_days| is_order_too_old(order, max_age_days));
+        if too_old {
+            self.old_order_count += 1;
+        }
+        too_old
+    }
 
-    if orders.is_empty() {
-        eprintln!("No orders parsed from CSV");
-        return None;
+    /// Adds a buy order we could sell into, unless its minimum volume blocks us. With
+    /// `Flag` handling the blocked orders still count.
+    fn push_buy(&mut self, order: &OrderRow) {
+        if order.min_volume > self.profile.units_to_sell {
+            self.min_volume_blocked_count += 1;
+            self.min_volume_blocked_best_price = self.min_volume_blocked_best_price.max(order.price);
+            if self.profile.min_volume_handling == MinVolumeHandling::Exclude {
+                return;
+            }
+        }
+        self.buy.push(order);
+        add_to_station(&mut self.stations, order);
+    }
+
+    /// Classifies a buy order against the selling station, once it has been located or
+    /// the whole book has been seen without it.
+    fn push_located_buy(&mut self, order: &OrderRow, station_id: i64) {
+        let reachability =
+            buy_order_reachability(order, station_id, self.sell_station_location.as_ref());
+        if let Some(summary) = &mut self.buy_reachability {
+            match reachability {
+                Reachability::Reachable => summary.reachable += 1,
+                Reachability::Uncertain => summary.uncertain += 1,
+                Reachability::Unreachable => summary.unreachable += 1,
+            }
+        }
+
+        let selected = match self.buy_range {
+            // Only count orders we are sure to be able to sell into
+            OrderRange::Reachable => reachability == Reachability::Reachable,
+            range => is_in_range(order, range, self.buy_hub_ids),
+        };
+        if selected && !self.is_too_old(order) {
+            self.push_buy(order);
+        }
     }
 
-    // Extract item name from file path (would need to be passed separately)
-    // For now, we'll set a placeholder
-    if item_name.is_empty() {
-        item_name = format!("Type ID {}", type_id);
+    fn resolve_pending_buy_orders(&mut self) {
+        let Some(station_id) = self.profile.sell_station_id else {
+            return;
+        };
+        for order in std::mem::take(&mut self.pending_buy_orders) {
+            self.push_located_buy(&order, station_id);
+        }
+        if let Some(summary) = &mut self.buy_reachability {
+            summary.station_found = self.sell_station_location.is_some();
+        }
     }
 
-    let buy_range_enum: OrderRange = buy_range.into();
-    let sell_range_enum: OrderRange = sell_range.into();
+    fn finish(
+        mut self,
+        item_name: String,
+        type_id: i32,
+        region: Option<String>,
+        exported_at: Option<DateTime<Utc>>,
+    ) -> MarketData {
+        self.resolve_pending_buy_orders();
+        let profile = self.profile;
+        let sell = self.sell.finish();
+        let buy = self.buy.finish();
 
-    let sell_orders = filter_orders_by_range(&orders, sell_range_enum, false);
-    let buy_orders = filter_orders_by_range(&orders, buy_range_enum, true);
+        let export_age_minutes =
+            exported_at.map(|exported_at| (Utc::now() - exported_at).num_seconds() as f64 / 60.0);
+        // For sell orders, use lower bound of CI (to avoid undercuts)
+        let sell_price_95_ci = calculate_normal_ci(&sell).0;
+        // For buy orders, use upper bound of CI (to avoid price hikes)
+        let buy_price_95_ci = calculate_normal_ci(&buy).1;
+        let suggested_sell_price = pricing::undercut_price(sell.lowest_price());
+        let suggested_buy_price = pricing::overbid_price(buy.highest_price());
 
-    let sell_price = sell_orders
-        .iter()
-        .map(|o| o.price)
-        .min_by(|a, b| a.partial_cmp(b).unwrap())
-        .unwrap_or(-1.0);
+        MarketData {
+            item_name,
+            type_id,
+            region,
+            exported_at,
+            sell_price: sell.lowest_price(),
+            buy_price: buy.highest_price(),
+            suggested_sell_price,
+            suggested_buy_price,
+            sell_order_count: sell.count(),
+            buy_order_count: buy.count(),
+            sell_price_95_ci,
+            buy_price_95_ci,
+            suggested_sell_price_95_ci: if sell_price_95_ci >= 0.0 {
+                pricing::round_to_tick(sell_price_95_ci, false)
+            } else {
+                suggested_sell_price
+            },
+            suggested_buy_price_95_ci: if buy_price_95_ci >= 0.0 {
+                pricing::round_to_tick(buy_price_95_ci, true)
+            } else {
+                suggested_buy_price
+            },
+            sell_total_quantity: sell.total_quantity,
+            buy_total_quantity: buy.total_quantity,
+            sell_total_isk_value: sell.total_isk_value,
+            buy_total_isk_value: buy.total_isk_value,
+            depth: DepthPrices {
+                target: profile.depth_target,
+                sell: depth_weighted_price(&sell.levels, profile.depth_target, false),
+                buy: depth_weighted_price(&buy.levels, profile.depth_target, true),
+            },
+            sell_price_statistics: calculate_price_statistics(&sell),
+            buy_price_statistics: calculate_price_statistics(&buy),
+            price_estimator: profile.price_estimator,
+            sell_price_estimate: estimate_price(&sell, profile.price_estimator, false),
+            buy_price_estimate: estimate_price(&buy, profile.price_estimator, true),
+            buy_reachability: self.buy_reachability,
+            min_volume_blocked_count: self.min_volume_blocked_count,
+            min_volume_blocked_best_price: self.min_volume_blocked_best_price,
+            old_order_count: self.old_order_count,
+            export_age_minutes,
+            is_stale: export_age_minutes.is_some_and(|age| age > profile.stale_after_minutes),
+            stations: sort_stations(self.stations),
+            calculated: pricing::calculate_profit(sell.lowest_price(), buy.highest_price(), profile),
+            warnings: self.warnings,
+        }
+    }
+}
 
-    let buy_price = buy_orders
-        .iter()
-        .map(|o| o.price)
-        .max_by(|a, b| a.partial_cmp(b).unwrap())
-        .unwrap_or(-1.0);
-
-    // Calculate 95% confidence interval prices
-    let sell_prices: Vec<f64> = sell_orders.iter().map(|o| o.price).collect();
-    let buy_prices: Vec<f64> = buy_orders.iter().map(|o| o.price).collect();
-    
-    // For sell orders, use lower bound of CI (to avoid undercuts)
-    let sell_price_95_ci = if sell_prices.is_empty() {
-        -1.0
-    } else {
-        calculate_95_ci_lower(&sell_prices)
-    };
-    
-    // For buy orders, use upper bound of CI (to avoid price hikes)
-    let buy_price_95_ci = if buy_prices.is_empty() {
-        -1.0
-    } else {
-        calculate_95_ci_upper(&buy_prices)
-    };
+fn calculate_mean(prices: &[f64]) -> f64 {
+    if prices.is_empty() {
+        return 0.0;
+    }
+    prices.iter().sum::<f64>() / prices.len() as f64
+}
 
-    // Calculate total quantity and ISK value for sell orders
-    let sell_total_quantity: f64 = sell_orders.iter().map(|o| o.quantity).sum();
-    let sell_total_isk_value: f64 = sell_orders.iter().map(|o| o.price * o.quantity).sum();
+/// 95% confidence interval of the mean using the normal approximation.
+fn calculate_normal_ci(side: &SideStats) -> (f64, f64) {
+    if side.count() < 2 {
+        let price = side.prices.first().copied().unwrap_or(-1.0);
+        return (price, price);
+    }
+    let standard_error = side.std_dev / (side.count() as f64).sqrt();
+    // Z-score for 95% confidence is 1.96
+    let margin = 1.96 * standard_error;
+    (side.mean - margin, side.mean + margin)
+}
 
-    // Calculate total quantity and ISK value for buy orders
-    let buy_total_quantity: f64 = buy_orders.iter().map(|o| o.quantity).sum();
-    let buy_total_isk_value: f64 = buy_orders.iter().map(|o| o.price * o.quantity).sum();
+/// Two-sided 95% critical values of Student's t for 1 to 30 degrees of freedom.
+const T_975: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+    2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+    2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
 
-    Some(MarketData {
-        item_name,
-        type_id,
-        sell_price,
-        buy_price,
-        sell_order_count: sell_orders.len(),
-        buy_order_count: buy_orders.len(),
-        sell_price_95_ci,
-        buy_price_95_ci,
-        sell_total_quantity,
-        buy_total_quantity,
-        sell_total_isk_value,
-        buy_total_isk_value,
-    })
+fn t_critical_975(degrees_of_freedom: usize) -> f64 {
+    if degrees_of_freedom == 0 {
+        return f64::NAN;
+    }
+    if let Some(&t) = T_975.get(degrees_of_freedom - 1) {
+        return t;
+    }
+    // Cornish-Fisher expansion around the normal quantile, accurate to 3 decimals past 30 df
+    let z: f64 = 1.959964;
+    let df = degrees_of_freedom as f64;
+    z + (z.powi(3) + z) / (4.0 * df) + (5.0 * z.powi(5) + 16.0 * z.powi(3) + 3.0 * z) / (96.0 * df * df)
 }
 
-pub fn extract_item_name_from_filename(filename: &str) -> String {
-    // Filename format: location-typeid-itemname.txt
-    let parts: Vec<&str> = filename.split('-').collect();
-    if parts.len() <= 2 {
-        return "Unknown".to_string();
+fn calculate_t_ci(side: &SideStats) -> (f64, f64) {
+    if side.count() < 2 {
+        let price = side.prices.first().copied().unwrap_or(-1.0);
+        return (price, price);
     }
+    let standard_error = side.std_dev / (side.count() as f64).sqrt();
+    let margin = t_critical_975(side.count() - 1) * standard_error;
+    (side.mean - margin, side.mean + margin)
+}
 
-    // Remove first (location) and last (typeid.txt) parts
-    let name_parts = &parts[1..parts.len() - 1];
-    let name = name_parts.join("-");
+/// Linear interpolation between closest ranks; `sorted` must be ascending and non-empty.
+fn calculate_percentile(sorted: &[f64], percentile: f64) -> f64 {
+    let rank = percentile.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
 
-    if name.is_empty() {
-        "Unknown".to_string()
+fn calculate_trimmed_mean(sorted: &[f64], trim_percent: f64) -> f64 {
+    let trim = (sorted.len() as f64 * trim_percent.clamp(0.0, 50.0) / 100.0).floor() as usize;
+    let kept = &sorted[trim..sorted.len() - trim];
+    if kept.is_empty() {
+        calculate_percentile(sorted, 50.0)
     } else {
-        name
+        calculate_mean(kept)
+    }
+}
+
+fn calculate_median_absolute_deviation(sorted: &[f64], median: f64) -> f64 {
+    let mut deviations: Vec<f64> = sorted.iter().map(|p| (p - median).abs()).collect();
+    deviations.sort_by(f64::total_cmp);
+    calculate_percentile(&deviations, 50.0)
+}
+
+/// `levels` must be sorted ascending by price.
+fn calculate_volume_weighted_quantile(levels: &[PriceLevel], total_volume: f64, quantile: f64) -> f64 {
+    if total_volume <= 0.0 {
+        return levels.first().map_or(-1.0, |level| level.price);
+    }
+
+    let threshold = quantile.clamp(0.0, 1.0) * total_volume;
+    let mut cumulative = 0.0;
+    for level in levels {
+        cumulative += level.volume;
+        if cumulative >= threshold {
+            return level.price;
+        }
+    }
+    levels.last().map_or(-1.0, |level| level.price)
+}
+
+fn calculate_price_statistics(side: &SideStats) -> Option<PriceStatistics> {
+    if side.prices.is_empty() {
+        return None;
+    }
+    let sorted = &side.prices;
+    let median = calculate_percentile(sorted, 50.0);
+    let (t_ci_lower, t_ci_upper) = calculate_t_ci(side);
+
+    Some(PriceStatistics {
+        count: sorted.len(),
+        mean: side.mean,
+        std_dev: side.std_dev,
+        median,
+        p10: calculate_percentile(sorted, 10.0),
+        p25: calculate_percentile(sorted, 25.0),
+        p75: calculate_percentile(sorted, 75.0),
+        p90: calculate_percentile(sorted, 90.0),
+        trimmed_mean_10: calculate_trimmed_mean(sorted, 10.0),
+        median_absolute_deviation: calculate_median_absolute_deviation(sorted, median),
+        volume_weighted_median: calculate_volume_weighted_quantile(
+            &side.levels,
+            side.total_quantity,
+            0.5,
+        ),
+        t_ci_lower,
+        t_ci_upper,
+    })
+}
+
+fn estimate_price(side: &SideStats, estimator: PriceEstimator, is_buy: bool) -> f64 {
+    if side.prices.is_empty() {
+        return -1.0;
+    }
+    let sorted = &side.prices;
+
+    match estimator {
+        PriceEstimator::NormalCi => {
+            let (lower, upper) = calculate_normal_ci(side);
+            if is_buy {
+                upper
+            } else {
+                lower
+            }
+        }
+        PriceEstimator::TDistributionCi => {
+            let (lower, upper) = calculate_t_ci(side);
+            if is_buy {
+                upper
+            } else {
+                lower
+            }
+        }
+        PriceEstimator::Median => calculate_percentile(sorted, 50.0),
+        PriceEstimator::Percentile { percentile } => {
+            let percentile = if is_buy { 100.0 - percentile } else { percentile };
+            calculate_percentile(sorted, percentile)
+        }
+        PriceEstimator::TrimmedMean { trim_percent } => calculate_trimmed_mean(sorted, trim_percent),
+        PriceEstimator::MedianAbsoluteDeviation { threshold } => {
+            let median = calculate_percentile(sorted, 50.0);
+            // 1.4826 scales the MAD to match the standard deviation of normal data
+            let scaled_mad = 1.4826 * calculate_median_absolute_deviation(sorted, median);
+            let mut inliers = sorted
+                .iter()
+                .copied()
+                .filter(|p| scaled_mad == 0.0 || (p - median).abs() <= threshold * scaled_mad);
+            let best = if is_buy { inliers.next_back() } else { inliers.next() };
+            best.unwrap_or(median)
+        }
+        PriceEstimator::VolumeWeightedQuantile { quantile } => {
+            let quantile = if is_buy { 1.0 - quantile } else { quantile };
+            calculate_volume_weighted_quantile(&side.levels, side.total_quantity, quantile)
+        }
+    }
+}
+
+/// The text encoding a market log file turned out to be in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LogEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+impl std::fmt::Display for LogEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogEncoding::Utf8 => write!(f, "UTF-8"),
+            LogEncoding::Utf16Le => write!(f, "UTF-16LE"),
+            LogEncoding::Utf16Be => write!(f, "UTF-16BE"),
+            LogEncoding::Latin1 => write!(f, "Latin-1"),
+        }
+    }
+}
+
+/// Decodes the raw bytes of a market log. The English client writes UTF-8, but other
+/// locales and editors that touched the file may leave a byte order mark, switch to
+/// UTF-16 or use an 8-bit code page; anything else is read as Latin-1, which never fails.
+pub fn decode_market_log(bytes: &[u8]) -> (String, LogEncoding) {
+    if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        // An editor may have marked the file UTF-8 and still saved 8-bit characters
+        return match std::str::from_utf8(rest) {
+            Ok(text) => (text.to_string(), LogEncoding::Utf8),
+            Err(_) => (decode_latin1(rest), LogEncoding::Latin1),
+        };
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFF\xFE") {
+        return (decode_utf16(rest, u16::from_le_bytes), LogEncoding::Utf16Le);
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFE\xFF") {
+        return (decode_utf16(rest, u16::from_be_bytes), LogEncoding::Utf16Be);
+    }
+
+    // Checked before UTF-8 because UTF-16 ASCII is also valid UTF-8, full of NULs
+    match sniff_utf16(bytes) {
+        Some(LogEncoding::Utf16Le) => return (decode_utf16(bytes, u16::from_le_bytes), LogEncoding::Utf16Le),
+        Some(LogEncoding::Utf16Be) => return (decode_utf16(bytes, u16::from_be_bytes), LogEncoding::Utf16Be),
+        _ => {}
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => (text.to_string(), LogEncoding::Utf8),
+        Err(_) => (decode_latin1(bytes), LogEncoding::Latin1),
+    }
+}
+
+fn decode_latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| char::from(b)).collect()
+}
+
+fn decode_utf16(bytes: &[u8], to_unit: fn([u8; 2]) -> u16) -> String {
+    let units = bytes.chunks_exact(2).map(|pair| to_unit([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// Recognizes UTF-16 without a byte order mark from the header line, which is plain
+/// ASCII and so has a zero in every high byte.
+fn sniff_utf16(bytes: &[u8]) -> Option<LogEncoding> {
+    let sample = &bytes[..bytes.len().min(256) & !1];
+    if sample.is_empty() {
+        return None;
+    }
+    let pairs = sample.len() / 2;
+    let zeros_at = |offset: usize| sample.iter().skip(offset).step_by(2).filter(|&&b| b == 0).count();
+
+    match (zeros_at(0), zeros_at(1)) {
+        (0, odd) if odd * 10 >= pairs * 9 => Some(LogEncoding::Utf16Le),
+        (even, 0) if even * 10 >= pairs * 9 => Some(LogEncoding::Utf16Be),
+        _ => None,
+    }
+}
+
+/// Picks the field separator from the header line; clients that write decimal commas
+/// may separate fields with semicolons or tabs instead.
+fn sniff_delimiter(start: &[u8]) -> u8 {
+    let header = start.split(|&b| b == b'\n').next().unwrap_or_default();
+    [b',', b';', b'\t']
+        .into_iter()
+        .find(|delimiter| header.contains(delimiter))
+        .unwrap_or(b',')
+}
+
+/// Parses a market log export into its order book, reporting every row that was
+/// skipped and why. The report is returned even when the whole file is rejected.
+///
+/// The item name, region and export time come from `file_name`; order ages are
+/// measured from that export time so re-reading an old export gives the same result.
+pub fn parse_market_log(
+    csv_content: &str,
+    file_name: &str,
+) -> (Result<OrderBook, MarketLogError>, ParseReport) {
+    let log_file_name = MarketLogFileName::parse(file_name);
+    let mut report = ParseReport {
+        file_name: file_name.to_string(),
+        ..ParseReport::default()
+    };
+    let mut orders = Vec::new();
+    let reference = log_file_name.exported_at.unwrap_or_else(Utc::now);
+    let result = read_orders(csv_content.as_bytes(), reference, &mut report, |order| {
+        orders.push(order)
+    })
+    .map(|type_id| OrderBook {
+        item_name: log_file_name.item_name,
+        type_id,
+        region: log_file_name.region,
+        exported_at: log_file_name.exported_at,
+        orders,
+    });
+    if let Err(e) = &result {
+        report.error = Some(e.to_string());
+    }
+    (result, report)
+}
+
+/// Summarizes a market log straight from the reader without building the order book,
+/// for callers that only need the summary, like `market_analyzer`. Gives the same
+/// result as `parse_market_log` followed by `summarize_order_book`. The reader must
+/// yield UTF-8, see `decode_market_log` for files in other encodings.
+pub fn summarize_market_log<R: std::io::Read>(
+    reader: R,
+    file_name: &str,
+    profile: &Profile,
+    station_groups: &StationGroups,
+) -> (Result<MarketData, MarketLogError>, ParseReport) {
+    let log_file_name = MarketLogFileName::parse(file_name);
+    let mut report = ParseReport {
+        file_name: file_name.to_string(),
+        ..ParseReport::default()
+    };
+    let mut accumulator = MarketAccumulator::new(profile, station_groups);
+    let reference = log_file_name.exported_at.unwrap_or_else(Utc::now);
+    let result = read_orders(reader, reference, &mut report, |order| {
+        accumulator.push(Cow::Owned(order))
+    })
+        .map(|type_id| {
+            accumulator.finish(
+                log_file_name.item_name,
+                type_id,
+                log_file_name.region,
+                log_file_name.exported_at,
+            )
+        });
+    match &result {
+        Ok(market_data) => report.warnings.extend(market_data.warnings.iter().cloned()),
+        Err(e) => report.error = Some(e.to_string()),
+    }
+    (result, report)
+}
+
+/// Reads every order from a market log, handing each to `on_order` as soon as it is
+/// parsed. Returns the item's type ID.
+fn read_orders<R: std::io::Read>(
+    reader: R,
+    reference: DateTime<Utc>,
+    report: &mut ParseReport,
+    mut on_order: impl FnMut(OrderRow),
+) -> Result<i32, MarketLogError> {
+    let mut reader = std::io::BufReader::new(reader);
+    let start = reader.fill_buf().map_err(|e| MarketLogError::Csv(e.to_string()))?;
+    let delimiter = sniff_delimiter(start);
+    let decimal = sniff_decimal_separator(start, delimiter);
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(reader);
+
+    let header = reader
+        .headers()
+        .map_err(|e| MarketLogError::Csv(e.to_string()))?
+        .clone();
+    let columns = ColumnMap::from_header(&header)?;
+
+    if !columns.unknown_columns.is_empty() {
+        report.warnings.push(format!(
+            "Ignoring unknown columns: {}",
+            columns.unknown_columns.join(", ")
+        ));
+    }
+    if !columns.missing_optional_columns.is_empty() {
+        report.warnings.push(format!(
+            "Missing optional columns, using defaults: {}",
+            columns.missing_optional_columns.join(", ")
+        ));
+    }
+
+    let expected_columns = header.len();
+    let mut type_id = -1;
+    let mut mixed_type_rows = 0;
+
+    // One record buffer for the whole file instead of an allocation per row
+    let mut record = csv::StringRecord::new();
+    loop {
+        match reader.read_record(&mut record) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => {
+                report.total_rows += 1;
+                let line = e.position().map_or(0, |p| p.line());
+                report.reject(line, RowRejectReason::Unreadable { message: e.to_string() });
+                continue;
+            }
+        }
+        report.total_rows += 1;
+        let line = record.position().map_or(0, |p| p.line());
+
+        match parse_csv_row(&record, &columns, expected_columns, decimal) {
+            Ok(mut order) => {
+                if type_id == -1 {
+                    type_id = order.type_id;
+                } else if order.type_id != type_id {
+                    mixed_type_rows += 1;
+                }
+                order.refresh_age(reference);
+                on_order(order);
+                report.parsed_rows += 1;
+            }
+            Err(reason) => report.reject(line, reason),
+        }
+    }
+
+    if mixed_type_rows > 0 {
+        report.warnings.push(format!(
+            "{} orders have a different typeID than {}",
+            mixed_type_rows, type_id
+        ));
+    }
+    if report.skipped_rows > report.rejections.len() {
+        report.warnings.push(format!(
+            "Only the first {} of {} rejected rows are listed",
+            report.rejections.len(),
+            report.skipped_rows
+        ));
+    }
+
+    if report.parsed_rows == 0 {
+        return Err(MarketLogError::NoOrders);
+    }
+    Ok(type_id)
+}
+
+/// `levels` must be sorted ascending by price.
+fn depth_weighted_price(levels: &[PriceLevel], target: DepthTarget, is_buy: bool) -> Option<DepthPrice> {
+    let target_quantity = match target {
+        DepthTarget::Quantity(quantity) => quantity,
+        DepthTarget::DepthPercent(percent) => {
+            levels.iter().map(|level| level.volume).sum::<f64>() * percent.clamp(0.0, 100.0) / 100.0
+        }
+    };
+    if levels.is_empty() || target_quantity <= 0.0 {
+        return None;
+    }
+
+    // Fill from the best price outwards: lowest sell orders, highest buy orders
+    let best_first = (0..levels.len()).map(|i| {
+        if is_buy {
+            &levels[levels.len() - 1 - i]
+        } else {
+            &levels[i]
+        }
+    });
+
+    let mut filled_quantity = 0.0;
+    let mut total_isk_value = 0.0;
+    let mut worst_price = 0.0;
+    for level in best_first {
+        if filled_quantity >= target_quantity {
+            break;
+        }
+        let take = level.volume.min(target_quantity - filled_quantity);
+        if take <= 0.0 {
+            continue;
+        }
+        filled_quantity += take;
+        total_isk_value += take * level.price;
+        worst_price = level.price;
+    }
+
+    if filled_quantity <= 0.0 {
+        return None;
+    }
+
+    Some(DepthPrice {
+        target_quantity,
+        filled_quantity,
+        average_price: total_isk_value / filled_quantity,
+        worst_price,
+        total_isk_value,
+        fully_filled: filled_quantity >= target_quantity,
+    })
+}
+
+/// Finds sell orders that can be bought and immediately sold into a buy order at a
+/// profit, matching volumes greedily from the widest spread inwards. Costs are charged
+/// like `pricing::calculate_profit` does: the profile's buy broker fee on the purchase
+/// and sales tax on the sale.
+pub fn find_arbitrage(book: &OrderBook, profile: &Profile) -> Vec<ArbitrageOpportunity> {
+    let broker_fee_rate = pricing::buy_broker_fee(profile);
+    let sales_tax_rate = pricing::sales_tax(profile.accounting);
+
+    let mut sell_orders: Vec<(&OrderRow, f64)> = book
+        .orders
+        .iter()
+        .filter(|order| !order.bid && order.vol_remaining > 0.0)
+        .map(|order| (order, order.vol_remaining))
+        .collect();
+    sell_orders.sort_by(|a, b| a.0.price.total_cmp(&b.0.price));
+
+    let mut buy_orders: Vec<&OrderRow> = book
+        .orders
+        .iter()
+        .filter(|order| order.bid && order.vol_remaining > 0.0)
+        .collect();
+    buy_orders.sort_by(|a, b| b.price.total_cmp(&a.price));
+
+    let mut opportunities = Vec::new();
+    for buy_order in buy_orders {
+        let net_sell_price = buy_order.price * (1.0 - sales_tax_rate);
+        let mut buy_remaining = buy_order.vol_remaining;
+
+        for (sell_order, sell_remaining) in sell_orders.iter_mut() {
+            let cost_per_unit = sell_order.price * (1.0 + broker_fee_rate);
+            if buy_remaining <= 0.0 || cost_per_unit >= net_sell_price {
+                break;
+            }
+            let quantity = sell_remaining.min(buy_remaining);
+            if quantity <= 0.0 || quantity < buy_order.min_volume {
+                continue;
+            }
+
+            let location = StationLocation::of(sell_order);
+            let (sell_at_station_id, min_jumps, max_jumps) =
+                match buy_order_reachability(buy_order, sell_order.station_id, Some(&location)) {
+                    Reachability::Reachable => (sell_order.station_id, 0, 0),
+                    _ => {
+                        let (min_jumps, max_jumps) = location.jumps_to(&StationLocation::of(buy_order));
+                        (buy_order.station_id, min_jumps, max_jumps)
+                    }
+                };
+
+            let broker_fee = sell_order.price * quantity * broker_fee_rate;
+            let sales_tax = buy_order.price * quantity * sales_tax_rate;
+            let net_profit = (net_sell_price - cost_per_unit) * quantity;
+            opportunities.push(ArbitrageOpportunity {
+                buy_from_order_id: sell_order.order_id,
+                buy_from_station_id: sell_order.station_id,
+                buy_price: sell_order.price,
+                sell_to_order_id: buy_order.order_id,
+                sell_to_station_id: buy_order.station_id,
+                sell_price: buy_order.price,
+                sell_at_station_id,
+                quantity,
+                min_jumps,
+                max_jumps,
+                broker_fee,
+                sales_tax,
+                net_profit,
+                net_profit_per_unit: net_profit / quantity,
+            });
+
+            *sell_remaining -= quantity;
+            buy_remaining -= quantity;
+        }
+    }
+
+    opportunities.sort_by(|a, b| b.net_profit.total_cmp(&a.net_profit));
+    opportunities
+}
+
+/// Groups the book by station, best place to sell (highest bid) first.
+/// Every station in the export, regardless of the profile's order ranges.
+pub fn summarize_stations(book: &OrderBook) -> Vec<StationSummary> {
+    let mut stations: HashMap<i64, StationSummary> = HashMap::new();
+    for order in &book.orders {
+        add_to_station(&mut stations, order);
+    }
+    sort_stations(stations)
+}
+
+fn add_to_station(stations: &mut HashMap<i64, StationSummary>, order: &OrderRow) {
+    let station = stations.entry(order.station_id).or_insert_with(|| StationSummary {
+        station_id: order.station_id,
+        solar_system_id: order.solar_system_id,
+        jumps: order.jumps,
+        best_bid: -1.0,
+        best_ask: -1.0,
+        buy_order_count: 0,
+        sell_order_count: 0,
+        buy_total_quantity: 0.0,
+        sell_total_quantity: 0.0,
+        buy_total_isk_value: 0.0,
+        sell_total_isk_value: 0.0,
+    });
+
+    if order.bid {
+        station.best_bid = station.best_bid.max(order.price);
+        station.buy_order_count += 1;
+        station.buy_total_quantity += order.vol_remaining;
+        station.buy_total_isk_value += order.price * order.vol_remaining;
+    } else {
+        if station.best_ask < 0.0 || order.price < station.best_ask {
+            station.best_ask = order.price;
+        }
+        station.sell_order_count += 1;
+        station.sell_total_quantity += order.vol_remaining;
+        station.sell_total_isk_value += order.price * order.vol_remaining;
+    }
+}
+
+fn sort_stations(stations: HashMap<i64, StationSummary>) -> Vec<StationSummary> {
+    let mut stations: Vec<StationSummary> = stations.into_values().collect();
+    stations.sort_by(|a, b| {
+        b.best_bid
+            .total_cmp(&a.best_bid)
+            .then(a.jumps.cmp(&b.jumps))
+            .then(a.station_id.cmp(&b.station_id))
+    });
+    stations
+}
+
+/// Depth-weighted prices for both sides of the book, using the profile's order ranges.
+pub fn calculate_depth_prices(
+    book: &OrderBook,
+    profile: &Profile,
+    station_groups: &StationGroups,
+    target: DepthTarget,
+) -> DepthPrices {
+    let mut accumulator = MarketAccumulator::new(profile, station_groups);
+    for order in &book.orders {
+        accumulator.push(Cow::Borrowed(order));
+    }
+    accumulator.resolve_pending_buy_orders();
+
+    DepthPrices {
+        target,
+        sell: depth_weighted_price(&accumulator.sell.finish().levels, target, false),
+        buy: depth_weighted_price(&accumulator.buy.finish().levels, target, true),
+    }
+}
+
+pub fn summarize_order_book(
+    book: &OrderBook,
+    profile: &Profile,
+    station_groups: &StationGroups,
+) -> MarketData {
+    let mut accumulator = MarketAccumulator::new(profile, station_groups);
+    for order in &book.orders {
+        accumulator.push(Cow::Borrowed(order));
+    }
+    accumulator.finish(
+        book.item_name.clone(),
+        book.type_id,
+        book.region.clone(),
+        book.exported_at,
+    )
+}
+
+/// The parts of a market log filename, which EVE writes as
+/// `Region-Item Name-YYYY.MM.DD HHMMSS.txt` using EVE time (UTC).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketLogFileName {
+    pub region: Option<String>,
+    pub item_name: String,
+    pub exported_at: Option<DateTime<Utc>>,
+}
+
+impl MarketLogFileName {
+    pub fn parse(filename: &str) -> Self {
+        let stem = filename
+            .strip_suffix(".txt")
+            .or_else(|| filename.strip_suffix(".TXT"))
+            .unwrap_or(filename)
+            .trim();
+
+        // The timestamp never contains a hyphen, so it is everything after the last one
+        let (rest, exported_at) = match stem.rsplit_once('-') {
+            Some((rest, timestamp)) => match parse_export_timestamp(timestamp) {
+                Some(exported_at) => (rest, Some(exported_at)),
+                None => (stem, None),
+            },
+            None => (stem, None),
+        };
+
+        let (region, item_name) = match split_region(rest) {
+            Some((region, item_name)) if exported_at.is_some() => {
+                (Some(region.to_string()), item_name)
+            }
+            _ => (None, rest),
+        };
+
+        let item_name = item_name.trim();
+        MarketLogFileName {
+            region,
+            item_name: if item_name.is_empty() {
+                "Unknown".to_string()
+            } else {
+                item_name.to_string()
+            },
+            exported_at,
+        }
+    }
+
+    /// The export time, or when `entry` was last written for renamed exports, which
+    /// lose their timestamp.
+    pub fn exported_at_or_modified(&self, entry: &std::fs::DirEntry) -> Option<DateTime<Utc>> {
+        self.exported_at.or_else(|| {
+            entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .map(DateTime::<Utc>::from)
+        })
+    }
+}
+
+fn parse_export_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    // Anything after the seconds (e.g. " (1)" on a duplicate export) is ignored
+    let timestamp = timestamp.trim().get(..17)?;
+    NaiveDateTime::parse_from_str(timestamp, "%Y.%m.%d %H%M%S")
+        .ok()
+        .map(|naive| naive.and_utc())
+}
+
+/// Splits `Region-Item Name` at the end of the region. Only wormhole regions
+/// (`A-R00001`) and Pochven's `VR-01` style names contain a hyphen themselves,
+/// item names are free to contain as many as they like.
+fn split_region(name: &str) -> Option<(&str, &str)> {
+    let bytes = name.as_bytes();
+    let is_digits = |range: std::ops::Range<usize>| {
+        bytes.get(range).is_some_and(|b| b.iter().all(u8::is_ascii_digit))
+    };
+
+    let region_len = if bytes.len() > 8
+        && bytes[0].is_ascii_uppercase()
+        && &bytes[1..3] == b"-R"
+        && is_digits(3..8)
+        && bytes[8] == b'-'
+    {
+        8
+    } else if bytes.len() > 5 && &bytes[..3] == b"VR-" && is_digits(3..5) && bytes[5] == b'-' {
+        5
+    } else {
+        name.find('-')?
+    };
+
+    Some((&name[..region_len], &name[region_len + 1..]))
+}
+
+/// Market log fixtures shared by the tests of the modules built on the parser.
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::*;
+
+    pub const HEADER: &str = "price,volRemaining,typeID,range,orderID,volEntered,minVolume,bid,issueDate,duration,stationID,regionID,solarSystemID,jumps,";
+
+    pub const SELL_ROW: &str = "5.5,2000.0,34,-1,6800000002,2000,1,False,2026-10-18 09:00:00.000,90,60003760,10000002,30000142,0,";
+    pub const BUY_ROW: &str = "5.1,1000.0,34,32767,6800000001,5000,1,True,2026-10-18 10:00:00.000,90,60003760,10000002,30000142,0,";
+
+    pub fn market_log(rows: &[&str]) -> String {
+        let mut content = format!("{}\n", HEADER);
+        for row in rows {
+            content.push_str(row);
+            content.push('\n');
+        }
+        content
+    }
+
+    /// The summary of an export of `rows` named `file_name`, under the default profile.
+    pub fn market_data(file_name: &str, rows: &[&str]) -> MarketData {
+        let (book, _) = parse_market_log(&market_log(rows), file_name);
+        summarize_order_book(
+            &book.unwrap(),
+            &Profile::default("Default".to_string()),
+            &StationGroups::default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::{market_log, BUY_ROW, HEADER, SELL_ROW};
+    use super::*;
+    use crate::station_groups::TRADE_HUBS_GROUP;
+
+    fn record(line: &str) -> csv::StringRecord {
+        csv::StringRecord::from(line.split(',').collect::<Vec<_>>())
+    }
+
+    fn parse_line(header: &str, line: &str) -> Result<OrderRow, RowRejectReason> {
+        let header = record(header);
+        let columns = ColumnMap::from_header(&header).unwrap();
+        parse_csv_row(&record(line), &columns, header.len(), DecimalSeparator::Dot)
+    }
+
+    #[test]
+    fn reads_columns_by_header_name_in_any_order() {
+        let order = parse_line(
+            "jumps,stationID,bid,typeID,volRemaining,price,orderID",
+            "3,60003760,True,34,1500.0,5.25,6800000001",
+        )
+        .unwrap();
+        assert_eq!(order.jumps, 3);
+        assert_eq!(order.station_id, 60003760);
+        assert!(order.bid);
+        assert_eq!(order.type_id, 34);
+        assert_eq!(order.vol_remaining, 1500.0);
+        assert_eq!(order.price, 5.25);
+        assert_eq!(order.order_id, 6800000001);
+    }
+
+    #[test]
+    fn header_names_ignore_case_whitespace_and_bom() {
+        let order = parse_line(
+            "\u{feff}PRICE, volremaining ,TypeId,bid,stationid,Jumps",
+            "5.25,10,34,False,60003760,0",
+        )
+        .unwrap();
+        assert_eq!(order.price, 5.25);
+        assert_eq!(order.vol_remaining, 10.0);
+        assert!(!order.bid);
+    }
+
+    #[test]
+    fn missing_optional_columns_fall_back_to_defaults() {
+        let header = record("price,volRemaining,typeID,bid,stationID,jumps,");
+        let columns = ColumnMap::from_header(&header).unwrap();
+        assert_eq!(columns.width, 6);
+        assert!(columns.missing_optional_columns.contains(&"orderID".to_string()));
+        assert!(columns.unknown_columns.is_empty());
+
+        let row = record("5.25,10,34,True,60003760,2,");
+        let order = parse_csv_row(&row, &columns, header.len(), DecimalSeparator::Dot).unwrap();
+        assert_eq!(order.order_id, 0);
+        assert_eq!(order.range, 0);
+        assert_eq!(order.min_volume, 1.0);
+        assert_eq!(order.solar_system_id, 0);
+        assert_eq!(order.issue_date, None);
+    }
+
+    #[test]
+    fn missing_required_columns_are_all_reported() {
+        let result = ColumnMap::from_header(&record("price,volRemaining,typeID,bid,"));
+        assert_eq!(
+            result.err(),
+            Some(MarketLogError::MissingColumns(vec![
+                "stationID".to_string(),
+                "jumps".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn data_line_instead_of_header_is_missing_header() {
+        let result = ColumnMap::from_header(&record(
+            "5.1,1000.0,34,32767,6800000001,5000,1,True,2026-10-18 10:00:00.000,90,60003760,10000002,30000142,0,",
+        ));
+        assert_eq!(result.err(), Some(MarketLogError::MissingHeader));
+    }
+
+    #[test]
+    fn unknown_columns_are_listed() {
+        let columns = ColumnMap::from_header(&record(&format!("{}escrow", HEADER))).unwrap();
+        assert_eq!(columns.unknown_columns, vec!["escrow".to_string()]);
+        assert!(columns.missing_optional_columns.is_empty());
+    }
+
+    #[test]
+    fn short_rows_are_rejected_with_column_counts() {
+        let result = parse_line(HEADER, "5.1,1000.0,34");
+        assert_eq!(
+            result.err(),
+            Some(RowRejectReason::WrongColumnCount { expected: 15, found: 3 })
+        );
+    }
+
+    #[test]
+    fn bad_cells_name_the_offending_value() {
+        let row = |price: &str, jumps: &str| {
+            format!(
+                "{},1000.0,34,32767,6800000001,5000,1,True,2026-10-18 10:00:00.000,90,60003760,10000002,30000142,{},",
+                price, jumps
+            )
+        };
+        assert_eq!(
+            parse_line(HEADER, &row("abc", "0")).err(),
+            Some(RowRejectReason::BadPrice { value: "abc".to_string() })
+        );
+        assert_eq!(
+            parse_line(HEADER, &row("-1", "0")).err(),
+            Some(RowRejectReason::BadPrice { value: "-1".to_string() })
+        );
+        assert_eq!(parse_line(HEADER, &row("5.1", "")).err(), Some(RowRejectReason::MissingJumps));
+        assert_eq!(
+            parse_line(HEADER, &row("5.1", "x")).err(),
+            Some(RowRejectReason::BadJumps { value: "x".to_string() })
+        );
+
+        let order = parse_line(HEADER, &row("5.1", "0")).unwrap();
+        assert_eq!(order.range, 32767);
+        assert_eq!(order.expires_at.unwrap().to_rfc3339(), "2027-01-16T10:00:00+00:00");
+    }
+
+    #[test]
+    fn report_counts_parsed_and_skipped_rows() {
+        let content = market_log(&[
+            BUY_ROW,
+            "oops,1000.0,34,32767,6800000003,5000,1,True,2026-10-18 10:00:00.000,90,60003760,10000002,30000142,0,",
+            SELL_ROW,
+            "5.1,1000.0",
+        ]);
+        let (result, report) = parse_market_log(&content, "");
+
+        assert_eq!(result.unwrap().orders.len(), 2);
+        assert_eq!(report.total_rows, 4);
+        assert_eq!(report.parsed_rows, 2);
+        assert_eq!(report.skipped_rows, 2);
+        assert_eq!(report.rejections.len(), 2);
+        assert_eq!(report.rejections[0].line, 3);
+        assert_eq!(report.rejections[0].reason, RowRejectReason::BadPrice { value: "oops".to_string() });
+        assert_eq!(report.rejections[1].line, 5);
+        assert!(report.warnings.is_empty());
+        assert_eq!(report.error, None);
+    }
+
+    #[test]
+    fn report_lists_only_the_first_rejections() {
+        let rows = vec!["x,1,34,0,1,1,1,True,,90,60003760,10000002,30000142,0,"; MAX_REPORTED_REJECTIONS + 5];
+        let content = market_log(&[&rows[..], &[SELL_ROW]].concat());
+        let (result, report) = parse_market_log(&content, "");
+
+        assert!(result.is_ok());
+        assert_eq!(report.total_rows, MAX_REPORTED_REJECTIONS + 6);
+        assert_eq!(report.parsed_rows, 1);
+        assert_eq!(report.skipped_rows, MAX_REPORTED_REJECTIONS + 5);
+        assert_eq!(report.rejections.len(), MAX_REPORTED_REJECTIONS);
+        assert_eq!(
+            report.warnings,
+            vec![format!(
+                "Only the first {} of {} rejected rows are listed",
+                MAX_REPORTED_REJECTIONS,
+                MAX_REPORTED_REJECTIONS + 5
+            )]
+        );
+    }
+
+    #[test]
+    fn report_keeps_counts_when_no_row_parses() {
+        let (result, report) = parse_market_log(&market_log(&["5.1,1000.0"]), "");
+        assert_eq!(result.err(), Some(MarketLogError::NoOrders));
+        assert_eq!(report.total_rows, 1);
+        assert_eq!(report.skipped_rows, 1);
+        assert_eq!(report.error, Some(MarketLogError::NoOrders.to_string()));
+
+        let (result, report) = parse_market_log("", "");
+        assert!(result.is_err());
+        assert_eq!(report.total_rows, 0);
+        assert!(report.error.is_some());
+    }
+
+    fn file_name(region: Option<&str>, item_name: &str, exported_at: Option<&str>) -> MarketLogFileName {
+        MarketLogFileName {
+            region: region.map(str::to_string),
+            item_name: item_name.to_string(),
+            exported_at: exported_at.map(|at| DateTime::parse_from_rfc3339(at).unwrap().to_utc()),
+        }
+    }
+
+    #[test]
+    fn file_name_splits_region_item_and_export_time() {
+        assert_eq!(
+            MarketLogFileName::parse("Sinq Laison-Tritanium-2026.10.18 110509.txt"),
+            file_name(Some("Sinq Laison"), "Tritanium", Some("2026-10-18T11:05:09Z"))
+        );
+    }
+
+    #[test]
+    fn file_name_keeps_hyphens_in_item_names() {
+        assert_eq!(
+            MarketLogFileName::parse("The Forge-Inherent Implants 'Noble' Repair Systems RS-601-2026.10.18 110000.txt"),
+            file_name(
+                Some("The Forge"),
+                "Inherent Implants 'Noble' Repair Systems RS-601",
+                Some("2026-10-18T11:00:00Z")
+            )
+        );
+    }
+
+    #[test]
+    fn file_name_recognizes_regions_containing_hyphens() {
+        assert_eq!(
+            MarketLogFileName::parse("A-R00001-Tritanium-2026.10.18 110000.txt"),
+            file_name(Some("A-R00001"), "Tritanium", Some("2026-10-18T11:00:00Z"))
+        );
+        assert_eq!(
+            MarketLogFileName::parse("VR-01-Mid-grade Amulet Alpha-2026.10.18 110000.txt"),
+            file_name(Some("VR-01"), "Mid-grade Amulet Alpha", Some("2026-10-18T11:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn file_name_ignores_duplicate_suffix_and_extension_case() {
+        assert_eq!(
+            MarketLogFileName::parse("Domain-Tritanium-2026.10.18 110000 (1).TXT"),
+            file_name(Some("Domain"), "Tritanium", Some("2026-10-18T11:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn renamed_file_is_all_item_name() {
+        assert_eq!(
+            MarketLogFileName::parse("tritanium jita.txt"),
+            file_name(None, "tritanium jita", None)
+        );
+        // Without a timestamp there is no telling a region from a hyphenated item name
+        assert_eq!(
+            MarketLogFileName::parse("The Forge-Tritanium.txt"),
+            file_name(None, "The Forge-Tritanium", None)
+        );
+        assert_eq!(
+            MarketLogFileName::parse("The Forge-Tritanium-yesterday.txt"),
+            file_name(None, "The Forge-Tritanium-yesterday", None)
+        );
+        assert_eq!(MarketLogFileName::parse(".txt"), file_name(None, "Unknown", None));
+    }
+
+    fn levels(levels: &[(f64, f64)]) -> Vec<PriceLevel> {
+        levels
+            .iter()
+            .map(|&(price, volume)| PriceLevel { price, volume })
+            .collect()
+    }
+
+    #[test]
+    fn depth_price_fills_sells_from_the_cheapest_order() {
+        let book = levels(&[(10.0, 100.0), (11.0, 100.0), (15.0, 1000.0)]);
+        let depth = depth_weighted_price(&book, DepthTarget::Quantity(150.0), false).unwrap();
+        assert_eq!(depth.filled_quantity, 150.0);
+        assert_eq!(depth.total_isk_value, 1550.0);
+        assert!((depth.average_price - 1550.0 / 150.0).abs() < 1e-9);
+        assert_eq!(depth.worst_price, 11.0);
+        assert!(depth.fully_filled);
+    }
+
+    #[test]
+    fn depth_price_fills_buys_from_the_highest_order() {
+        let book = levels(&[(8.0, 1000.0), (9.0, 50.0), (10.0, 50.0)]);
+        let depth = depth_weighted_price(&book, DepthTarget::Quantity(200.0), true).unwrap();
+        assert_eq!(depth.total_isk_value, 500.0 + 450.0 + 800.0);
+        assert_eq!(depth.worst_price, 8.0);
+        assert!(depth.fully_filled);
+    }
+
+    #[test]
+    fn depth_price_reports_a_partial_fill() {
+        let book = levels(&[(10.0, 100.0), (12.0, 50.0)]);
+        let depth = depth_weighted_price(&book, DepthTarget::Quantity(500.0), false).unwrap();
+        assert_eq!(depth.target_quantity, 500.0);
+        assert_eq!(depth.filled_quantity, 150.0);
+        assert_eq!(depth.total_isk_value, 1600.0);
+        assert_eq!(depth.worst_price, 12.0);
+        assert!(!depth.fully_filled);
+    }
+
+    #[test]
+    fn depth_percent_is_taken_from_the_side_volume() {
+        let book = levels(&[(10.0, 100.0), (12.0, 300.0)]);
+        let depth = depth_weighted_price(&book, DepthTarget::DepthPercent(50.0), false).unwrap();
+        assert_eq!(depth.target_quantity, 200.0);
+        assert_eq!(depth.total_isk_value, 1000.0 + 1200.0);
+        assert!(depth.fully_filled);
+    }
+
+    #[test]
+    fn depth_price_needs_orders_and_a_target() {
+        assert!(depth_weighted_price(&[], DepthTarget::Quantity(10.0), false).is_none());
+        let book = levels(&[(10.0, 100.0)]);
+        assert!(depth_weighted_price(&book, DepthTarget::Quantity(0.0), false).is_none());
+        assert!(depth_weighted_price(&book, DepthTarget::DepthPercent(0.0), true).is_none());
+    }
+
+    #[test]
+    fn price_estimator_uses_camel_case_fields() {
+        let estimator = PriceEstimator::TrimmedMean { trim_percent: 10.0 };
+        let json = serde_json::to_value(estimator).unwrap();
+        assert_eq!(json, serde_json::json!({ "kind": "trimmedMean", "trimPercent": 10.0 }));
+        assert_eq!(serde_json::from_value::<PriceEstimator>(json).unwrap(), estimator);
+    }
+
+    #[test]
+    fn price_estimator_round_trips_every_variant() {
+        for estimator in [
+            PriceEstimator::NormalCi,
+            PriceEstimator::TDistributionCi,
+            PriceEstimator::Median,
+            PriceEstimator::Percentile { percentile: 20.0 },
+            PriceEstimator::TrimmedMean { trim_percent: 10.0 },
+            PriceEstimator::MedianAbsoluteDeviation { threshold: 3.0 },
+            PriceEstimator::VolumeWeightedQuantile { quantile: 0.25 },
+        ] {
+            let json = serde_json::to_string(&estimator).unwrap();
+            assert_eq!(serde_json::from_str::<PriceEstimator>(&json).unwrap(), estimator, "{}", json);
+        }
+    }
+
+    fn order(bid: bool, price: f64, station_id: i64, solar_system_id: i64, jumps: i32) -> OrderRow {
+        OrderRow {
+            price,
+            vol_remaining: 100.0,
+            type_id: 34,
+            range: if bid { RANGE_REGION } else { RANGE_STATION },
+            order_id: 0,
+            vol_entered: 100.0,
+            min_volume: 1.0,
+            bid,
+            issue_date: None,
+            duration: 90,
+            station_id,
+            region_id: 10000002,
+            solar_system_id,
+            jumps,
+            expires_at: None,
+            age_days: None,
+            expires_in_days: None,
+        }
+    }
+
+    fn book(orders: Vec<OrderRow>) -> OrderBook {
+        OrderBook {
+            item_name: "Tritanium".to_string(),
+            type_id: 34,
+            region: None,
+            exported_at: None,
+            orders,
+        }
+    }
+
+    const JITA_4_4: i64 = 60003760;
+    const JITA: i64 = 30000142;
+
+    #[test]
+    fn hub_range_needs_the_player_in_the_hub_system() {
+        let groups = StationGroups::default();
+        let hub_ids = groups.station_ids(TRADE_HUBS_GROUP).unwrap();
+        assert!(is_in_range(&order(false, 5.0, JITA_4_4, JITA, 0), OrderRange::Hub, hub_ids));
+        assert!(!is_in_range(&order(false, 5.0, JITA_4_4, JITA, 3), OrderRange::Hub, hub_ids));
+        assert!(!is_in_range(&order(false, 5.0, 60003466, JITA, 0), OrderRange::Hub, hub_ids));
+    }
+
+    #[test]
+    fn unknown_hub_group_is_reported_instead_of_guessed() {
+        let mut profile = Profile::default("Test".to_string());
+        profile.sell_hub_group = "Deleted Group".to_string();
+        let orders = book(vec![order(false, 5.0, JITA_4_4, JITA, 0), order(true, 4.0, JITA_4_4, JITA, 0)]);
+
+        let market_data = summarize_order_book(&orders, &profile, &StationGroups::default());
+        assert_eq!(market_data.sell_order_count, 0);
+        assert_eq!(market_data.buy_order_count, 1);
+        assert_eq!(
+            market_data.warnings,
+            vec!["Station group 'Deleted Group' does not exist, no sell orders are at a hub".to_string()]
+        );
+
+        // The group only matters when the range uses it
+        profile.sell_range = OrderRange::Region as u8;
+        let market_data = summarize_order_book(&orders, &profile, &StationGroups::default());
+        assert_eq!(market_data.sell_order_count, 1);
+        assert!(market_data.warnings.is_empty());
+    }
+
+    /// A normal bid at 4.0 and a better one at 4.5 that needs 10 units, with the default
+    /// profile selling a single unit.
+    fn min_volume_book() -> OrderBook {
+        book(vec![
+            order(true, 4.0, JITA_4_4, JITA, 0),
+            OrderRow {
+                min_volume: 10.0,
+                ..order(true, 4.5, JITA_4_4, JITA, 0)
+            },
+        ])
+    }
+
+    #[test]
+    fn min_volume_exclude_drops_blocked_bids() {
+        let mut profile = Profile::default("Test".to_string());
+        profile.min_volume_handling = MinVolumeHandling::Exclude;
+
+        let market_data = summarize_order_book(&min_volume_book(), &profile, &StationGroups::default());
+        assert_eq!(market_data.buy_price, 4.0);
+        assert_eq!(market_data.buy_order_count, 1);
+        assert_eq!(market_data.buy_total_quantity, 100.0);
+        assert_eq!(market_data.min_volume_blocked_count, 1);
+        assert_eq!(market_data.min_volume_blocked_best_price, 4.5);
+    }
+
+    #[test]
+    fn min_volume_flag_keeps_blocked_bids() {
+        let mut profile = Profile::default("Test".to_string());
+        profile.min_volume_handling = MinVolumeHandling::Flag;
+
+        let market_data = summarize_order_book(&min_volume_book(), &profile, &StationGroups::default());
+        assert_eq!(market_data.buy_price, 4.5);
+        assert_eq!(market_data.buy_order_count, 2);
+        assert_eq!(market_data.buy_total_quantity, 200.0);
+        assert_eq!(market_data.min_volume_blocked_count, 1);
+        assert_eq!(market_data.min_volume_blocked_best_price, 4.5);
+    }
+
+    #[test]
+    fn min_volume_within_units_to_sell_is_not_blocked() {
+        let mut profile = Profile::default("Test".to_string());
+        profile.units_to_sell = 10.0;
+
+        let market_data = summarize_order_book(&min_volume_book(), &profile, &StationGroups::default());
+        assert_eq!(market_data.buy_price, 4.5);
+        assert_eq!(market_data.buy_order_count, 2);
+        assert_eq!(market_data.min_volume_blocked_count, 0);
+        assert_eq!(market_data.min_volume_blocked_best_price, -1.0);
+    }
+
+    #[test]
+    fn station_table_covers_stations_in_range() {
+        const PERIMETER: i64 = 30000144;
+        const PERIMETER_TTT: i64 = 1028858195912;
+        const AMARR_8: i64 = 60008494;
+        let with_volume = |order: OrderRow, vol_remaining: f64| OrderRow { vol_remaining, ..order };
+        let orders = book(vec![
+            order(false, 5.5, JITA_4_4, JITA, 1),
+            order(false, 5.2, JITA_4_4, JITA, 1),
+            with_volume(order(true, 4.8, JITA_4_4, JITA, 1), 50.0),
+            with_volume(order(false, 5.3, PERIMETER_TTT, PERIMETER, 2), 20.0),
+            order(true, 4.9, PERIMETER_TTT, PERIMETER, 2),
+            order(true, 4.7, PERIMETER_TTT, PERIMETER, 2),
+            // More than two jumps away
+            order(true, 6.0, AMARR_8, 30002187, 12),
+        ]);
+        let mut profile = Profile::default("Test".to_string());
+        profile.sell_range = OrderRange::TwoJump as u8;
+        profile.buy_range = OrderRange::TwoJump as u8;
+
+        let stations = summarize_order_book(&orders, &profile, &StationGroups::default()).stations;
+        let ids: Vec<i64> = stations.iter().map(|station| station.station_id).collect();
+        // Best bid first
+        assert_eq!(ids, vec![PERIMETER_TTT, JITA_4_4]);
+
+        let perimeter = &stations[0];
+        assert_eq!((perimeter.best_bid, perimeter.best_ask), (4.9, 5.3));
+        assert_eq!((perimeter.buy_order_count, perimeter.sell_order_count), (2, 1));
+        assert_eq!((perimeter.buy_total_quantity, perimeter.sell_total_quantity), (200.0, 20.0));
+        assert_eq!(perimeter.jumps, 2);
+
+        let jita = &stations[1];
+        assert_eq!((jita.best_bid, jita.best_ask), (4.8, 5.2));
+        assert_eq!((jita.buy_order_count, jita.sell_order_count), (1, 2));
+        assert_eq!((jita.buy_total_quantity, jita.sell_total_quantity), (50.0, 200.0));
+        assert!((jita.sell_total_isk_value - 1070.0).abs() < 1e-9);
+
+        // The breakdown of the whole export still includes it
+        let all_stations = summarize_stations(&orders);
+        assert_eq!(all_stations[0].station_id, AMARR_8);
+        assert_eq!(all_stations.len(), 3);
+    }
+
+    fn buy_order(station_id: i64, solar_system_id: i64, jumps: i32, range: i32) -> OrderRow {
+        OrderRow {
+            range,
+            ..order(true, 4.0, station_id, solar_system_id, jumps)
+        }
+    }
+
+    fn location(solar_system_id: i64, jumps: i32) -> StationLocation {
+        StationLocation { solar_system_id, jumps }
+    }
+
+    #[test]
+    fn reachability_from_order_range_and_distance() {
+        let selling = location(JITA, 0);
+        let reach = |order: OrderRow| buy_order_reachability(&order, JITA_4_4, Some(&selling));
+
+        assert_eq!(reach(buy_order(JITA_4_4, JITA, 0, RANGE_STATION)), Reachability::Reachable);
+        assert_eq!(reach(buy_order(60003466, JITA, 0, RANGE_STATION)), Reachability::Unreachable);
+        assert_eq!(reach(buy_order(60003466, JITA, 0, 0)), Reachability::Reachable);
+        assert_eq!(reach(buy_order(60003466, 30000144, 2, 0)), Reachability::Unreachable);
+        assert_eq!(reach(buy_order(60003466, 30000144, 2, 2)), Reachability::Reachable);
+        assert_eq!(reach(buy_order(60003466, 30000144, 3, 2)), Reachability::Unreachable);
+        assert_eq!(reach(buy_order(60003466, 30000144, 9, RANGE_REGION)), Reachability::Reachable);
+    }
+
+    #[test]
+    fn reachability_between_two_remote_systems_is_bounded() {
+        let selling = location(30000144, 2);
+        let reach = |order: OrderRow| buy_order_reachability(&order, 60003466, Some(&selling));
+
+        // Somewhere between 1 and 5 jumps apart
+        assert_eq!(reach(buy_order(60000001, 30000145, 3, 5)), Reachability::Reachable);
+        assert_eq!(reach(buy_order(60000001, 30000145, 3, 2)), Reachability::Uncertain);
+        assert_eq!(reach(buy_order(60000001, 30000145, 3, 0)), Reachability::Unreachable);
+        let order = buy_order(60000001, 30000145, 3, 5);
+        assert_eq!(buy_order_reachability(&order, 60003466, None), Reachability::Uncertain);
+    }
+
+    #[test]
+    fn unknown_solar_systems_are_not_assumed_equal() {
+        let selling = location(UNKNOWN_SOLAR_SYSTEM, 5);
+        let reach = |order: OrderRow| buy_order_reachability(&order, 60003466, Some(&selling));
+
+        assert_eq!(reach(buy_order(60000001, UNKNOWN_SOLAR_SYSTEM, 5, 0)), Reachability::Uncertain);
+        assert_eq!(reach(buy_order(60000001, UNKNOWN_SOLAR_SYSTEM, 5, 2)), Reachability::Uncertain);
+        assert_eq!(reach(buy_order(60000001, UNKNOWN_SOLAR_SYSTEM, 4, 0)), Reachability::Unreachable);
+        assert_eq!(reach(buy_order(60000001, UNKNOWN_SOLAR_SYSTEM, 5, 10)), Reachability::Reachable);
+
+        // Both in the player's system, so the same one
+        let here = location(UNKNOWN_SOLAR_SYSTEM, 0);
+        let order = buy_order(60000001, UNKNOWN_SOLAR_SYSTEM, 0, 0);
+        assert_eq!(buy_order_reachability(&order, 60003466, Some(&here)), Reachability::Reachable);
+    }
+
+    #[test]
+    fn reachable_buy_range_without_selling_station_counts_nothing() {
+        let mut profile = Profile::default("Test".to_string());
+        profile.buy_range = OrderRange::Reachable as u8;
+        let orders = book(vec![order(true, 4.0, JITA_4_4, JITA, 0), order(false, 5.0, JITA_4_4, JITA, 0)]);
+
+        let market_data = summarize_order_book(&orders, &profile, &StationGroups::default());
+        assert_eq!(market_data.buy_order_count, 0);
+        assert_eq!(market_data.sell_order_count, 1);
+        assert_eq!(market_data.warnings.len(), 1);
+
+        profile.sell_station_id = Some(JITA_4_4);
+        let market_data = summarize_order_book(&orders, &profile, &StationGroups::default());
+        assert_eq!(market_data.buy_order_count, 1);
+        assert!(market_data.warnings.is_empty());
+    }
+
+    #[test]
+    fn order_ages_are_measured_from_the_export_time() {
+        let content = market_log(&[
+            "5.5,2000.0,34,-1,6800000002,2000,1,False,2020-01-01 12:00:00.000,90,60003760,10000002,30000142,0,",
+        ]);
+        let (result, report) = parse_market_log(&content, "The Forge-Tritanium-2020.01.03 120000.txt");
+        let book = result.unwrap();
+        assert_eq!(report.file_name, "The Forge-Tritanium-2020.01.03 120000.txt");
+        assert_eq!(book.item_name, "Tritanium");
+        assert_eq!(book.region.as_deref(), Some("The Forge"));
+        assert_eq!(book.orders[0].age_days, Some(2.0));
+        assert_eq!(book.orders[0].expires_in_days, Some(88.0));
+
+        let mut profile = Profile::default("Test".to_string());
+        profile.sell_range = OrderRange::Region as u8;
+        profile.max_order_age_days = Some(7.0);
+        let market_data = summarize_order_book(&book, &profile, &StationGroups::default());
+        assert_eq!(market_data.sell_order_count, 1);
+        assert_eq!(market_data.old_order_count, 0);
+        assert!(market_data.is_stale);
+    }
+
+    #[test]
+    fn old_order_count_only_counts_orders_that_were_left_out() {
+        let aged = |mut order: OrderRow, age_days: f64| {
+            order.age_days = Some(age_days);
+            order
+        };
+        let orders = book(vec![
+            aged(order(false, 5.0, JITA_4_4, JITA, 0), 30.0),
+            aged(order(false, 5.5, JITA_4_4, JITA, 0), 1.0),
+            // Out of the hub range either way
+            aged(order(false, 4.0, 60003466, 30000144, 2), 30.0),
+            aged(order(true, 4.5, 60003466, 30000144, 2), 30.0),
+        ]);
+        let mut profile = Profile::default("Test".to_string());
+        profile.max_order_age_days = Some(7.0);
+
+        let market_data = summarize_order_book(&orders, &profile, &StationGroups::default());
+        assert_eq!(market_data.sell_order_count, 1);
+        assert_eq!(market_data.buy_order_count, 0);
+        assert_eq!(market_data.old_order_count, 1);
+    }
+
+    #[test]
+    fn arbitrage_charges_broker_fee_and_sales_tax() {
+        let mut profile = Profile::default("Test".to_string());
+        profile.use_buy_custom_broker = true;
+        profile.buy_custom_broker = 0.01;
+        profile.accounting = 5;
+        let sales_tax_rate = pricing::sales_tax(5);
+
+        let sell_order = OrderRow { vol_remaining: 10.0, ..order(false, 100.0, JITA_4_4, JITA, 0) };
+        let buy_order = OrderRow { vol_remaining: 4.0, ..order(true, 110.0, JITA_4_4, JITA, 0) };
+        let opportunities = find_arbitrage(&book(vec![sell_order, buy_order]), &profile);
+
+        assert_eq!(opportunities.len(), 1);
+        let opportunity = &opportunities[0];
+        assert_eq!(opportunity.quantity, 4.0);
+        assert!((opportunity.broker_fee - 4.0).abs() < 1e-9);
+        assert!((opportunity.sales_tax - 440.0 * sales_tax_rate).abs() < 1e-9);
+        assert!((opportunity.net_profit - (440.0 - 400.0 - 4.0 - 440.0 * sales_tax_rate)).abs() < 1e-9);
+        assert_eq!(opportunity.sell_at_station_id, JITA_4_4);
+    }
+
+    #[test]
+    fn arbitrage_skips_spreads_eaten_by_fees() {
+        let mut profile = Profile::default("Test".to_string());
+        profile.use_buy_custom_broker = true;
+        profile.buy_custom_broker = 0.03;
+        profile.accounting = 5;
+
+        // 3% broker fee plus 3.4% sales tax is more than the 5% spread
+        let orders = book(vec![order(false, 100.0, JITA_4_4, JITA, 0), order(true, 105.0, JITA_4_4, JITA, 0)]);
+        assert!(find_arbitrage(&orders, &profile).is_empty());
+
+        profile.buy_custom_broker = 0.0;
+        assert_eq!(find_arbitrage(&orders, &profile).len(), 1);
+    }
+
+    /// A regional export where the selling station only shows up halfway through, so
+    /// buy orders before it have to wait for its location.
+    fn regional_market_log(rows: usize) -> String {
+        let lines: Vec<String> = (0..rows)
+            .map(|i| {
+                let bid = i % 2 == 0;
+                let station = if i < rows / 2 { i % 7 } else { i % 11 };
+                format!(
+                    "{:.2},{}.0,34,{},{},10000,{},{},2026-10-{:02} 10:00:00.000,90,{},10000002,{},{},",
+                    if bid { 4.0 } else { 5.0 } + (i * 37 % 100) as f64 / 100.0,
+                    100 + i * 13 % 900,
+                    if bid { [RANGE_STATION, 0, 1, 3, 5, RANGE_REGION][i % 6] } else { RANGE_STATION },
+                    6_800_000_000u64 + i as u64,
+                    1 + i % 3,
+                    if bid { "True" } else { "False" },
+                    1 + i % 17,
+                    60_003_000 + station,
+                    30_000_100 + station % 4,
+                    station % 4,
+                )
+            })
+            .collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        market_log(&lines)
+    }
+
+    const REGIONAL_FILE_NAME: &str = "The Forge-Tritanium-2026.10.18 120000.txt";
+
+    #[test]
+    fn streaming_summary_matches_parse_then_summarize() {
+        let content = regional_market_log(500);
+        let station_groups = StationGroups::default();
+
+        for buy_range in [OrderRange::Region, OrderRange::TwoJump, OrderRange::Reachable] {
+            let mut profile = Profile::default("Test".to_string());
+            profile.buy_range = buy_range as u8;
+            profile.sell_range = OrderRange::Region as u8;
+            profile.sell_station_id = Some(60_003_010);
+            profile.max_order_age_days = Some(10.0);
+            profile.units_to_sell = 2.0;
+            profile.price_estimator = PriceEstimator::MedianAbsoluteDeviation { threshold: 3.0 };
+
+            let (book, parse_report) = parse_market_log(&content, REGIONAL_FILE_NAME);
+            let parsed = summarize_order_book(&book.unwrap(), &profile, &station_groups);
+            let (streamed, stream_report) =
+                summarize_market_log(content.as_bytes(), REGIONAL_FILE_NAME, &profile, &station_groups);
+            let streamed = streamed.unwrap();
+
+            assert!(streamed.buy_reachability.as_ref().unwrap().station_found);
+            assert!(streamed.old_order_count > 0);
+            assert!(streamed.buy_order_count > 0);
+            assert_eq!(stream_report.parsed_rows, parse_report.parsed_rows);
+
+            // Only the age of the export depends on when each was computed
+            let json = |market_data: &MarketData| {
+                let mut json = serde_json::to_value(market_data).unwrap();
+                json["exportAgeMinutes"] = serde_json::Value::Null;
+                json
+            };
+            assert_eq!(json(&streamed), json(&parsed), "{:?}", buy_range);
+        }
+    }
+
+    #[test]
+    fn depth_prices_match_the_summary_depth() {
+        let (book, _) = parse_market_log(&regional_market_log(200), REGIONAL_FILE_NAME);
+        let book = book.unwrap();
+        let station_groups = StationGroups::default();
+        let mut profile = Profile::default("Test".to_string());
+        profile.buy_range = OrderRange::Reachable as u8;
+        profile.sell_range = OrderRange::TwoJump as u8;
+        profile.sell_station_id = Some(60_003_001);
+
+        let target = DepthTarget::Quantity(5_000.0);
+        let depth = calculate_depth_prices(&book, &profile, &station_groups, target);
+        profile.depth_target = target;
+        let summary = summarize_order_book(&book, &profile, &station_groups);
+
+        assert_eq!(
+            serde_json::to_value(&depth).unwrap(),
+            serde_json::to_value(&summary.depth).unwrap()
+        );
+        assert!(depth.sell.is_some() && depth.buy.is_some());
+    }
+
+    #[test]
+    fn numbers_follow_the_file_decimal_separator() {
+        use DecimalSeparator::{Comma, Dot};
+        assert_eq!(parse_number("1234.56", Dot), Some(1234.56));
+        assert_eq!(parse_number("1,500", Dot), Some(1500.0));
+        assert_eq!(parse_number("1,234,567.8", Dot), Some(1234567.8));
+        assert_eq!(parse_number("1 234.5", Dot), Some(1234.5));
+        assert_eq!(parse_number("1'234.5", Dot), Some(1234.5));
+
+        assert_eq!(parse_number("1234,56", Comma), Some(1234.56));
+        assert_eq!(parse_number("1,500", Comma), Some(1.5));
+        assert_eq!(parse_number("1.500", Comma), Some(1500.0));
+        assert_eq!(parse_number("1.234.567,8", Comma), Some(1234567.8));
+        assert_eq!(parse_number("1\u{a0}234,5", Comma), Some(1234.5));
+        assert_eq!(parse_number("1\u{202f}234,5", Comma), Some(1234.5));
+        assert_eq!(parse_number("42", Comma), Some(42.0));
+
+        assert_eq!(parse_number("", Dot), None);
+        assert_eq!(parse_number("abc", Comma), None);
+    }
+
+    #[test]
+    fn decimal_separator_is_sniffed_once_per_file() {
+        use DecimalSeparator::{Comma, Dot};
+        let sniff = |text: &str, delimiter: u8| sniff_decimal_separator(text.as_bytes(), delimiter);
+
+        assert_eq!(sniff("price,volRemaining\n5,1\n", b','), Dot);
+        assert_eq!(sniff("price;volRemaining\n5,10;1000,0\n", b';'), Comma);
+        assert_eq!(sniff("price;volRemaining\n5.10;1000.0\n", b';'), Dot);
+        assert_eq!(sniff("price\tvolRemaining\n1.234,5\t1\n", b'\t'), Comma);
+        // Three digits after the only separator could be either, so keep looking
+        assert_eq!(sniff("price;volRemaining\n1,500;2\n1,5;2\n", b';'), Comma);
+        assert_eq!(sniff("price;volRemaining\n1.500;2\n", b';'), Dot);
+        // Dates are not numbers
+        assert_eq!(sniff("issueDate;price\n2026-10-18 10:00:00.000;1,500\n", b';'), Dot);
+    }
+
+    #[test]
+    fn thousands_are_not_decimals_in_a_decimal_comma_file() {
+        let content = "price;volRemaining;typeID;bid;stationID;jumps;\n\
+                       1.500,25;2,0;34;False;60003760;0;\n\
+                       1,5;1.000,0;34;False;60003760;0;\n";
+        let (result, report) = parse_market_log(content, "");
+        let orders = result.unwrap().orders;
+        assert_eq!(report.skipped_rows, 0);
+        assert_eq!((orders[0].price, orders[0].vol_remaining), (1500.25, 2.0));
+        assert_eq!((orders[1].price, orders[1].vol_remaining), (1.5, 1000.0));
+    }
+
+    #[test]
+    fn delimiter_is_taken_from_the_header() {
+        assert_eq!(sniff_delimiter(HEADER.as_bytes()), b',');
+        assert_eq!(sniff_delimiter(b"price;volRemaining;typeID\n5,1;1;34"), b';');
+        assert_eq!(sniff_delimiter(b"price\tvolRemaining\n5,1\t1"), b'\t');
+        assert_eq!(sniff_delimiter(b"price\n5,1"), b',');
+        assert_eq!(sniff_delimiter(b""), b',');
+    }
+
+    fn utf16(text: &str, to_bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+        text.encode_utf16().flat_map(to_bytes).collect()
+    }
+
+    #[test]
+    fn utf16_without_byte_order_mark_is_recognized() {
+        let le = utf16(HEADER, u16::to_le_bytes);
+        let be = utf16(HEADER, u16::to_be_bytes);
+        assert_eq!(sniff_utf16(&le), Some(LogEncoding::Utf16Le));
+        assert_eq!(sniff_utf16(&be), Some(LogEncoding::Utf16Be));
+        assert_eq!(sniff_utf16(HEADER.as_bytes()), None);
+        assert_eq!(sniff_utf16(b""), None);
+
+        assert_eq!(decode_market_log(&le), (HEADER.to_string(), LogEncoding::Utf16Le));
+        assert_eq!(decode_market_log(&be), (HEADER.to_string(), LogEncoding::Utf16Be));
+    }
+
+    #[test]
+    fn byte_order_marks_are_stripped() {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(utf16("price,jumps", u16::to_le_bytes));
+        assert_eq!(decode_market_log(&bytes), ("price,jumps".to_string(), LogEncoding::Utf16Le));
+
+        let mut bytes = vec![0xFE, 0xFF];
+        bytes.extend(utf16("price,jumps", u16::to_be_bytes));
+        assert_eq!(decode_market_log(&bytes), ("price,jumps".to_string(), LogEncoding::Utf16Be));
+
+        assert_eq!(
+            decode_market_log(b"\xEF\xBB\xBFprice,jumps"),
+            ("price,jumps".to_string(), LogEncoding::Utf8)
+        );
+        // A UTF-8 BOM followed by 8-bit text falls back to Latin-1 without the BOM
+        assert_eq!(
+            decode_market_log(b"\xEF\xBB\xBFprice,Ni\xF1o"),
+            ("price,Ni\u{f1}o".to_string(), LogEncoding::Latin1)
+        );
+        assert_eq!(
+            decode_market_log(b"price,Ni\xF1o"),
+            ("price,Ni\u{f1}o".to_string(), LogEncoding::Latin1)
+        );
+    }
+
+    #[test]
+    fn confidence_interval_prices_are_rounded_onto_the_tick_grid() {
+        let mut profile = Profile::default("Test".to_string());
+        profile.buy_range = OrderRange::Region as u8;
+        profile.sell_range = OrderRange::Region as u8;
+        let orders = book(vec![
+            order(false, 1234.5, JITA_4_4, JITA, 0),
+            order(false, 1301.7, JITA_4_4, JITA, 0),
+            order(false, 1299.2, JITA_4_4, JITA, 0),
+            order(true, 98.37, JITA_4_4, JITA, 0),
+            order(true, 99.61, JITA_4_4, JITA, 0),
+        ]);
+        let market_data = summarize_order_book(&orders, &profile, &StationGroups::default());
+
+        assert!(market_data.sell_price_95_ci > 1000.0);
+        assert_eq!(
+            market_data.suggested_sell_price_95_ci,
+            market_data.sell_price_95_ci.floor()
+        );
+        // The upper bound crosses 100, where the tick grows to 0.1
+        assert!(market_data.buy_price_95_ci > 100.0);
+        assert_eq!(
+            market_data.suggested_buy_price_95_ci,
+            (market_data.buy_price_95_ci * 10.0).ceil() / 10.0
+        );
+
+        // A single order has no interval to round
+        let single = book(vec![order(false, 5.5, JITA_4_4, JITA, 0)]);
+        let market_data = summarize_order_book(&single, &profile, &StationGroups::default());
+        assert_eq!(market_data.suggested_sell_price_95_ci, 5.5);
+        assert_eq!(market_data.suggested_buy_price_95_ci, -1.0);
     }
 }
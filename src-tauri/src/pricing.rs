@@ -0,0 +1,289 @@
+use crate::market_parser::MarketData;
+use crate::profile::Profile;
+use serde::{Deserialize, Serialize};
+
+/// Per-unit fee and profit breakdown for buying just above the best buy order and
+/// reselling just below the best sell order, at the nearest prices EVE accepts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalculatedData {
+    pub revenue: f64,
+    pub cost_of_sales: f64,
+    pub profit: f64,
+    pub margin: f64,
+    pub markup: f64,
+    pub buy_order_cost: f64,
+    pub sell_order_cost: f64,
+}
+
+pub fn buy_broker_fee(profile: &Profile) -> f64 {
+    if profile.use_buy_custom_broker {
+        return profile.buy_custom_broker;
+    }
+    npc_broker(profile)
+}
+
+pub fn sell_broker_fee(profile: &Profile) -> f64 {
+    if profile.use_sell_custom_broker {
+        return profile.sell_custom_broker;
+    }
+    npc_broker(profile)
+}
+
+/// Broker fee at NPC stations, lowered by Broker Relations and standings.
+pub fn npc_broker(profile: &Profile) -> f64 {
+    (3.0 - (profile.broker_relations as f64 * 0.3
+        + profile.faction_standing * 0.03
+        + profile.corp_standing * 0.02))
+        / 100.0
+}
+
+/// Sales tax on completed sales, reduced by 11% per Accounting level.
+pub fn sales_tax(accounting: u8) -> f64 {
+    0.075 * (1.0 - accounting as f64 * 0.11)
+}
+
+/// Profit from overbidding `buy_price` and undercutting `sell_price` by one tick, the
+/// same prices as `MarketData`'s suggested prices.
+pub fn calculate_profit(sell_price: f64, buy_price: f64, profile: &Profile) -> CalculatedData {
+    let adjusted_sell_price = undercut_price(sell_price);
+    let adjusted_buy_price = overbid_price(buy_price);
+    if adjusted_sell_price < 0.0 || adjusted_buy_price < 0.0 {
+        return CalculatedData::default();
+    }
+
+    let buy_broker_fee_rate = buy_broker_fee(profile);
+    let sell_broker_fee_rate = sell_broker_fee(profile);
+    let sales_tax_rate = sales_tax(profile.accounting);
+
+    let revenue = adjusted_sell_price
+        - adjusted_sell_price * sell_broker_fee_rate
+        - adjusted_sell_price * sales_tax_rate;
+
+    let cost_of_sales = adjusted_buy_price + adjusted_buy_price * buy_broker_fee_rate;
+
+    let profit = revenue - cost_of_sales;
+
+    let buy_order_cost = adjusted_buy_price * buy_broker_fee_rate;
+    let sell_order_cost =
+        adjusted_sell_price * sell_broker_fee_rate + adjusted_sell_price * sales_tax_rate;
+
+    let margin = if revenue != 0.0 {
+        100.0 * (revenue - cost_of_sales) / revenue
+    } else {
+        0.0
+    };
+    let markup = if cost_of_sales != 0.0 {
+        100.0 * (revenue - cost_of_sales) / cost_of_sales
+    } else {
+        0.0
+    };
+
+    CalculatedData {
+        revenue,
+        cost_of_sales,
+        profit,
+        margin,
+        markup,
+        buy_order_cost,
+        sell_order_cost,
+    }
+}
+
+/// Profit breakdown for an export's best prices under `profile`.
+pub fn calculate(market_data: &MarketData, profile: &Profile) -> CalculatedData {
+    calculate_profit(market_data.sell_price, market_data.buy_price, profile)
+}
+
+/// Smallest price step EVE accepts at `price`: prices keep at most four significant
+/// figures, and never go finer than 0.01 ISK.
+pub fn tick_size(price: f64) -> f64 {
+    if price < 10.0 {
+        return 0.01;
+    }
+
+    let mut magnitude = price.log10().floor() as i32;
+    // log10 can land a hair off at exact powers of ten
+    if 10f64.powi(magnitude) > price {
+        magnitude -= 1;
+    } else if 10f64.powi(magnitude + 1) <= price {
+        magnitude += 1;
+    }
+    10f64.powi(magnitude - 3).max(0.01)
+}
+
+/// Rounds `price` onto the tick grid: down for sell orders, up for buy orders, so the
+/// order is never worse than the price we asked for.
+pub fn round_to_tick(price: f64, round_up: bool) -> f64 {
+    if price <= 0.0 {
+        return 0.0;
+    }
+
+    let tick = tick_size(price);
+    let steps = price / tick;
+    // Leave prices that are already legal alone despite floating point noise
+    let steps = if round_up {
+        (steps - 1e-6).ceil()
+    } else {
+        (steps + 1e-6).floor()
+    };
+    round_to_cents(steps * tick)
+}
+
+/// The highest legal sell price below `best_sell_price`, or -1 when there is none.
+pub fn undercut_price(best_sell_price: f64) -> f64 {
+    if best_sell_price <= 0.01 {
+        return -1.0;
+    }
+    let price = round_to_tick(best_sell_price, false);
+    if price < best_sell_price - 0.005 {
+        return price;
+    }
+    // Already on the grid: step down by the tick just below, which is finer at a power of ten
+    round_to_cents(price - tick_size(price - 0.01))
+}
+
+/// The lowest legal buy price above `best_buy_price`, or -1 when there is none.
+pub fn overbid_price(best_buy_price: f64) -> f64 {
+    if best_buy_price < 0.0 {
+        return -1.0;
+    }
+    let price = round_to_tick(best_buy_price, true);
+    if price > best_buy_price + 0.005 {
+        return price;
+    }
+    round_to_cents(price + tick_size(price))
+}
+
+fn round_to_cents(price: f64) -> f64 {
+    (price * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn profit_charges_broker_fees_and_sales_tax() {
+        let mut profile = Profile::default("Test".to_string());
+        profile.accounting = 0;
+        profile.use_buy_custom_broker = true;
+        profile.buy_custom_broker = 0.01;
+        profile.use_sell_custom_broker = true;
+        profile.sell_custom_broker = 0.02;
+
+        // Sells at 5.49 and buys at 5.11
+        let calculated = calculate_profit(5.5, 5.1, &profile);
+        assert_close(calculated.revenue, 5.49 * (1.0 - 0.02 - 0.075));
+        assert_close(calculated.cost_of_sales, 5.11 * 1.01);
+        assert_close(calculated.profit, calculated.revenue - calculated.cost_of_sales);
+        assert_close(calculated.margin, 100.0 * calculated.profit / calculated.revenue);
+        assert_close(calculated.markup, 100.0 * calculated.profit / calculated.cost_of_sales);
+        assert_close(calculated.buy_order_cost, 5.11 * 0.01);
+        assert_close(calculated.sell_order_cost, 5.49 * (0.02 + 0.075));
+    }
+
+    #[test]
+    fn profit_uses_npc_broker_fee_from_skills_and_standings() {
+        let mut profile = Profile::default("Test".to_string());
+        profile.accounting = 5;
+        profile.broker_relations = 5;
+        profile.faction_standing = 5.0;
+        profile.corp_standing = 5.0;
+
+        // 3% - 1.5% for Broker Relations - 0.15% and 0.1% for standings
+        let broker_fee_rate = 0.0125;
+        assert_close(npc_broker(&profile), broker_fee_rate);
+        let sales_tax_rate = 0.075 * 0.45;
+        assert_close(sales_tax(profile.accounting), sales_tax_rate);
+
+        let calculated = calculate_profit(5.5, 5.1, &profile);
+        assert_close(calculated.revenue, 5.49 * (1.0 - broker_fee_rate - sales_tax_rate));
+        assert_close(calculated.cost_of_sales, 5.11 * (1.0 + broker_fee_rate));
+    }
+
+    #[test]
+    fn profit_uses_the_suggested_prices_above_ten_isk() {
+        let mut profile = Profile::default("Test".to_string());
+        profile.use_buy_custom_broker = true;
+        profile.buy_custom_broker = 0.0;
+        profile.use_sell_custom_broker = true;
+        profile.sell_custom_broker = 0.0;
+        profile.accounting = 0;
+
+        let (sell_price, buy_price) = (1_234_567.0, 1_000_000.0);
+        let suggested_sell_price = undercut_price(sell_price);
+        let suggested_buy_price = overbid_price(buy_price);
+        assert_eq!((suggested_sell_price, suggested_buy_price), (1_234_000.0, 1_001_000.0));
+
+        let calculated = calculate_profit(sell_price, buy_price, &profile);
+        assert_close(calculated.revenue, suggested_sell_price * (1.0 - 0.075));
+        assert_close(calculated.cost_of_sales, suggested_buy_price);
+    }
+
+    #[test]
+    fn profit_needs_both_prices() {
+        let profile = Profile::default("Test".to_string());
+        assert_eq!(calculate_profit(-1.0, 5.1, &profile).revenue, 0.0);
+        assert_eq!(calculate_profit(5.5, -1.0, &profile).cost_of_sales, 0.0);
+        assert_eq!(calculate_profit(0.01, 0.0, &profile).profit, 0.0);
+    }
+
+    #[test]
+    fn tick_size_keeps_four_significant_figures() {
+        assert_eq!(tick_size(0.5), 0.01);
+        assert_eq!(tick_size(9.99), 0.01);
+        assert_eq!(tick_size(10.0), 0.01);
+        assert_eq!(tick_size(99.99), 0.01);
+        assert_eq!(tick_size(100.0), 0.1);
+        assert_eq!(tick_size(999.9), 0.1);
+        assert_eq!(tick_size(1000.0), 1.0);
+        assert_eq!(tick_size(1_234_567.0), 1000.0);
+        assert_eq!(tick_size(1e9), 1e6);
+    }
+
+    #[test]
+    fn round_to_tick_rounds_towards_the_safe_side() {
+        assert_eq!(round_to_tick(1_234_567.89, false), 1_234_000.0);
+        assert_eq!(round_to_tick(1_234_567.89, true), 1_235_000.0);
+        assert_eq!(round_to_tick(123.456, false), 123.4);
+        assert_eq!(round_to_tick(123.456, true), 123.5);
+        assert_eq!(round_to_tick(0.123456, false), 0.12);
+        assert_eq!(round_to_tick(0.123456, true), 0.13);
+        // Prices already on the grid stay put despite floating point noise
+        assert_eq!(round_to_tick(0.1 + 0.2, true), 0.3);
+        assert_eq!(round_to_tick(1001.0, false), 1001.0);
+        assert_eq!(round_to_tick(0.0, true), 0.0);
+    }
+
+    #[test]
+    fn undercut_and_overbid_cross_powers_of_ten() {
+        assert_eq!(overbid_price(99.99), 100.0);
+        assert_eq!(undercut_price(100.0), 99.99);
+        assert_eq!(overbid_price(999.9), 1000.0);
+        assert_eq!(undercut_price(1000.0), 999.9);
+        assert_eq!(overbid_price(1000.0), 1001.0);
+        assert_eq!(undercut_price(1_000_000.0), 999_900.0);
+    }
+
+    #[test]
+    fn undercut_and_overbid_snap_off_grid_prices() {
+        assert_eq!(undercut_price(1_234_567.0), 1_234_000.0);
+        assert_eq!(overbid_price(1_234_567.0), 1_235_000.0);
+        assert_eq!(undercut_price(5.5), 5.49);
+        assert_eq!(overbid_price(5.1), 5.11);
+    }
+
+    #[test]
+    fn prices_never_go_below_one_cent() {
+        assert_eq!(undercut_price(0.02), 0.01);
+        assert_eq!(undercut_price(0.01), -1.0);
+        assert_eq!(undercut_price(-1.0), -1.0);
+        assert_eq!(overbid_price(0.0), 0.01);
+        assert_eq!(overbid_price(-1.0), -1.0);
+    }
+}
@@ -1,3 +1,9 @@
+use crate::market_parser::{
+    default_depth_target, default_min_volume_handling, default_price_estimator,
+    default_stale_after_minutes, default_units_to_sell, DepthTarget, MinVolumeHandling,
+    PriceEstimator,
+};
+use crate::station_groups::default_hub_group;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -18,6 +24,24 @@ pub struct Profile {
     pub sell_custom_broker: f64,
     pub buy_range: u8,
     pub sell_range: u8,
+    #[serde(default = "default_depth_target")]
+    pub depth_target: DepthTarget,
+    #[serde(default = "default_price_estimator")]
+    pub price_estimator: PriceEstimator,
+    #[serde(default = "default_hub_group")]
+    pub buy_hub_group: String,
+    #[serde(default = "default_hub_group")]
+    pub sell_hub_group: String,
+    #[serde(default)]
+    pub sell_station_id: Option<i64>,
+    #[serde(default = "default_units_to_sell")]
+    pub units_to_sell: f64,
+    #[serde(default = "default_min_volume_handling")]
+    pub min_volume_handling: MinVolumeHandling,
+    #[serde(default)]
+    pub max_order_age_days: Option<f64>,
+    #[serde(default = "default_stale_after_minutes")]
+    pub stale_after_minutes: f64,
 }
 
 impl Profile {
@@ -37,6 +61,15 @@ impl Profile {
             sell_custom_broker: 0.01,
             buy_range: 0, // HUB
             sell_range: 0, // HUB
+            depth_target: default_depth_target(),
+            price_estimator: default_price_estimator(),
+            buy_hub_group: default_hub_group(),
+            sell_hub_group: default_hub_group(),
+            sell_station_id: None,
+            units_to_sell: default_units_to_sell(),
+            min_volume_handling: default_min_volume_handling(),
+            max_order_age_days: None,
+            stale_after_minutes: default_stale_after_minutes(),
         }
     }
 
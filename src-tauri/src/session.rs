@@ -0,0 +1,104 @@
+use crate::file_watcher::LogSource;
+use crate::market_parser::MarketData;
+use serde::{Deserialize, Serialize};
+
+/// The latest processed export of one item from one watched directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionEntry {
+    pub file_name: String,
+    pub market_data: MarketData,
+    pub source: LogSource,
+}
+
+/// Every item processed since the app started, including exports picked up by the
+/// startup scan, most recent export first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarketSession {
+    pub entries: Vec<SessionEntry>,
+}
+
+impl MarketSession {
+    /// Adds an export, replacing an older one of the same item in the same region from
+    /// the same directory. Each install keeps its own entry, as it may be summarized
+    /// with a different profile.
+    pub fn upsert(&mut self, file_name: String, market_data: MarketData, source: LogSource) {
+        let existing = self.entries.iter().position(|entry| {
+            entry.market_data.item_name == market_data.item_name
+                && entry.market_data.region == market_data.region
+                && entry.source.dir == source.dir
+        });
+        if let Some(index) = existing {
+            if self.entries[index].market_data.exported_at > market_data.exported_at {
+                return;
+            }
+            self.entries.remove(index);
+        }
+
+        self.entries.push(SessionEntry {
+            file_name,
+            market_data,
+            source,
+        });
+        self.entries
+            .sort_by_key(|entry| std::cmp::Reverse(entry.market_data.exported_at));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_parser::test_support::{market_data, SELL_ROW};
+    use std::path::PathBuf;
+
+    fn source(dir: &str) -> LogSource {
+        LogSource {
+            dir: PathBuf::from(dir),
+            profile: None,
+        }
+    }
+
+    fn upsert(session: &mut MarketSession, file_name: &str, dir: &str) {
+        session.upsert(file_name.to_string(), market_data(file_name, &[SELL_ROW]), source(dir));
+    }
+
+    fn file_names(session: &MarketSession) -> Vec<&str> {
+        session
+            .entries
+            .iter()
+            .map(|entry| entry.file_name.as_str())
+            .collect()
+    }
+
+    const OLDER: &str = "The Forge-Tritanium-2026.10.18 120000.txt";
+    const NEWER: &str = "The Forge-Tritanium-2026.10.18 130000.txt";
+
+    #[test]
+    fn newer_export_replaces_older_one() {
+        let mut session = MarketSession::default();
+        upsert(&mut session, OLDER, "/logs");
+        upsert(&mut session, NEWER, "/logs");
+        assert_eq!(file_names(&session), vec![NEWER]);
+    }
+
+    #[test]
+    fn older_export_is_ignored() {
+        let mut session = MarketSession::default();
+        upsert(&mut session, NEWER, "/logs");
+        upsert(&mut session, OLDER, "/logs");
+        assert_eq!(file_names(&session), vec![NEWER]);
+    }
+
+    #[test]
+    fn directories_keep_separate_entries() {
+        let mut session = MarketSession::default();
+        upsert(&mut session, OLDER, "/alt/logs");
+        upsert(&mut session, NEWER, "/logs");
+        upsert(&mut session, "Domain-Tritanium-2026.10.18 110000.txt", "/logs");
+        assert_eq!(
+            file_names(&session),
+            vec![NEWER, OLDER, "Domain-Tritanium-2026.10.18 110000.txt"]
+        );
+        assert_eq!(session.entries[1].source.dir, PathBuf::from("/alt/logs"));
+    }
+}
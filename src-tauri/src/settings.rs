@@ -15,6 +15,41 @@ pub struct AppSettings {
     pub window_width: Option<u32>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub window_height: Option<u32>,
+    /// How far back the startup scan picks up exports made while the app was closed;
+    /// 0 turns the scan off.
+    #[serde(default = "default_scan_lookback_hours")]
+    pub scan_lookback_hours: f64,
+    /// Poll the log directories instead of using OS file events, for network shares,
+    /// FUSE and Wine mounts that do not report changes.
+    #[serde(default)]
+    pub force_polling: bool,
+    /// How often the polling watcher rescans, in seconds.
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: f64,
+    /// Market logs directory chosen with "Select log path"; unset uses the client's default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_dir: Option<PathBuf>,
+    /// Market logs directories of other EVE installs, watched alongside `log_dir`.
+    #[serde(default)]
+    pub watched_dirs: Vec<WatchedDir>,
+}
+
+/// An extra market logs directory. Exports from it are priced with `profile` when set,
+/// otherwise with the selected profile.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedDir {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub profile: Option<String>,
+}
+
+pub fn default_scan_lookback_hours() -> f64 {
+    24.0
+}
+
+pub fn default_poll_interval_secs() -> f64 {
+    2.0
 }
 
 impl AppSettings {
@@ -27,6 +62,11 @@ impl AppSettings {
             window_y: None,
             window_width: None,
             window_height: None,
+            scan_lookback_hours: default_scan_lookback_hours(),
+            force_polling: false,
+            poll_interval_secs: default_poll_interval_secs(),
+            log_dir: None,
+            watched_dirs: Vec::new(),
         }
     }
 
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const TRADE_HUBS_GROUP: &str = "Trade Hubs";
+
+// Jita, Rens, Amarr, Dodixie, Hek
+const DEFAULT_HUB_IDS: [i64; 5] = [60003760, 60004588, 60008494, 60011866, 60005686];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StationGroup {
+    pub name: String,
+    pub station_ids: Vec<i64>,
+}
+
+/// Named sets of stations and structures used by the "Hub" order range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StationGroups {
+    pub groups: Vec<StationGroup>,
+}
+
+pub fn default_hub_group() -> String {
+    TRADE_HUBS_GROUP.to_string()
+}
+
+impl Default for StationGroups {
+    fn default() -> Self {
+        let group = |name: &str, station_ids: &[i64]| StationGroup {
+            name: name.to_string(),
+            station_ids: station_ids.to_vec(),
+        };
+
+        StationGroups {
+            groups: vec![
+                group(TRADE_HUBS_GROUP, &DEFAULT_HUB_IDS),
+                group("Jita", &[60003760]),
+                group("Rens", &[60004588]),
+                group("Amarr", &[60008494]),
+                group("Dodixie", &[60011866]),
+                group("Hek", &[60005686]),
+            ],
+        }
+    }
+}
+
+impl StationGroups {
+    pub fn get_station_groups_path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join("station_groups.json")
+    }
+
+    pub fn save(&self, app_data_dir: &Path) -> anyhow::Result<()> {
+        let path = Self::get_station_groups_path(app_data_dir);
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    pub fn load(app_data_dir: &Path) -> anyhow::Result<Self> {
+        let path = Self::get_station_groups_path(app_data_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        let station_groups: StationGroups = serde_json::from_str(&content)?;
+        Ok(station_groups)
+    }
+
+    /// Station IDs for a group, or `None` when it has been deleted since the profile
+    /// was saved.
+    pub fn station_ids(&self, name: &str) -> Option<&[i64]> {
+        self.groups
+            .iter()
+            .find(|group| group.name == name)
+            .map(|group| &group.station_ids[..])
+    }
+
+    pub fn upsert(&mut self, group: StationGroup) {
+        match self.groups.iter_mut().find(|g| g.name == group.name) {
+            Some(existing) => *existing = group,
+            None => self.groups.push(group),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> anyhow::Result<()> {
+        if name == TRADE_HUBS_GROUP {
+            return Err(anyhow::anyhow!("Cannot delete {} group", TRADE_HUBS_GROUP));
+        }
+
+        self.groups.retain(|group| group.name != name);
+        Ok(())
+    }
+}
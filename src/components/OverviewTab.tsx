@@ -1,11 +1,12 @@
 import { useMemo, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { Card, CardContent, CardHeader, CardTitle } from './ui/card';
 import { Separator } from './ui/separator';
 import { RadioGroup, RadioGroupItem } from './ui/radio-group';
 import { Label } from './ui/label';
 import { Switch } from './ui/switch';
-import { MarketData, Profile } from '@/lib/types';
-import { calculateProfit, formatISK, formatPercent, roundTo4SigFigs, formatQuantity } from '@/lib/calculations';
+import { CalculatedData, MarketData, Profile } from '@/lib/types';
+import { formatISK, formatPercent, formatQuantity } from '@/lib/calculations';
 import { cn } from '@/lib/utils';
 import { Copy, Check } from 'lucide-react';
 import { useState } from 'react';
@@ -30,11 +31,17 @@ export function OverviewTab({
   const [copied, setCopied] = useState(false);
   const [copiedMode, setCopiedMode] = useState<string | null>(null);
 
-  const calculated = useMemo(() => {
+  const [calculated, setCalculated] = useState<CalculatedData | null>(null);
+
+  // Recalculated rather than read from marketData.calculated so unsaved profile edits apply
+  useEffect(() => {
     if (!marketData) {
-      return null;
+      setCalculated(null);
+      return;
     }
-    return calculateProfit(marketData.sellPrice, marketData.buyPrice, profile);
+    invoke<CalculatedData>('calculate_profit', { marketData, profile })
+      .then(setCalculated)
+      .catch(console.error);
   }, [marketData, profile]);
 
   const handleCopy = async (text: string) => {
@@ -48,17 +55,13 @@ export function OverviewTab({
     
     switch (mode) {
       case 'sell':
-        return marketData.sellPrice >= 0 ? roundTo4SigFigs(marketData.sellPrice - 0.01, false) : null;
+        return marketData.suggestedSellPrice >= 0 ? String(marketData.suggestedSellPrice) : null;
       case 'buy':
-        return marketData.buyPrice >= 0 ? roundTo4SigFigs(marketData.buyPrice + 0.01, true) : null;
+        return marketData.suggestedBuyPrice >= 0 ? String(marketData.suggestedBuyPrice) : null;
       case 'sell95':
-        return marketData.sellPrice95Ci >= 0 
-          ? roundTo4SigFigs(marketData.sellPrice95Ci, false)
-          : (marketData.sellPrice >= 0 ? roundTo4SigFigs(marketData.sellPrice - 0.01, false) : null);
+        return marketData.suggestedSellPrice95Ci >= 0 ? String(marketData.suggestedSellPrice95Ci) : null;
       case 'buy95':
-        return marketData.buyPrice95Ci >= 0
-          ? roundTo4SigFigs(marketData.buyPrice95Ci, true)
-          : (marketData.buyPrice >= 0 ? roundTo4SigFigs(marketData.buyPrice + 0.01, true) : null);
+        return marketData.suggestedBuyPrice95Ci >= 0 ? String(marketData.suggestedBuyPrice95Ci) : null;
       default:
         return null;
     }
@@ -95,23 +98,19 @@ export function OverviewTab({
       let priceToCopy: string;
       switch (autoCopyMode) {
         case 'sell':
-          priceToCopy = roundTo4SigFigs(marketData.sellPrice - 0.01, false);
+          priceToCopy = String(marketData.suggestedSellPrice);
           break;
         case 'buy':
-          priceToCopy = roundTo4SigFigs(marketData.buyPrice + 0.01, true);
+          priceToCopy = String(marketData.suggestedBuyPrice);
           break;
         case 'sell95':
-          priceToCopy = marketData.sellPrice95Ci >= 0 
-            ? roundTo4SigFigs(marketData.sellPrice95Ci, false)
-            : roundTo4SigFigs(marketData.sellPrice - 0.01, false);
+          priceToCopy = String(marketData.suggestedSellPrice95Ci);
           break;
         case 'buy95':
-          priceToCopy = marketData.buyPrice95Ci >= 0
-            ? roundTo4SigFigs(marketData.buyPrice95Ci, true)
-            : roundTo4SigFigs(marketData.buyPrice + 0.01, true);
+          priceToCopy = String(marketData.suggestedBuyPrice95Ci);
           break;
         default:
-          priceToCopy = roundTo4SigFigs(marketData.sellPrice - 0.01, false);
+          priceToCopy = String(marketData.suggestedSellPrice);
       }
       handleCopy(priceToCopy);
     }
@@ -138,7 +137,7 @@ export function OverviewTab({
               className="cursor-pointer text-lg font-bold hover:opacity-80"
               onClick={() => {
                 if (marketData) {
-                  handleCopy(roundTo4SigFigs(marketData.sellPrice - 0.01, false));
+                  handleCopy(String(marketData.suggestedSellPrice));
                 }
               }}
             >
@@ -177,7 +176,7 @@ export function OverviewTab({
               className="cursor-pointer text-lg font-bold hover:opacity-80"
               onClick={() => {
                 if (marketData) {
-                  handleCopy(roundTo4SigFigs(marketData.buyPrice + 0.01, true));
+                  handleCopy(String(marketData.suggestedBuyPrice));
                 }
               }}
             >
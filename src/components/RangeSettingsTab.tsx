@@ -1,8 +1,9 @@
 import { Card, CardContent, CardHeader, CardTitle } from './ui/card';
 import { Label } from './ui/label';
+import { Input } from './ui/input';
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from './ui/select';
 import { Profile, OrderRange } from '@/lib/types';
-import { ORDER_RANGE_OPTIONS } from '@/lib/constants';
+import { BUY_ORDER_RANGE_OPTIONS, ORDER_RANGE_OPTIONS } from '@/lib/constants';
 
 interface RangeSettingsTabProps {
   profile: Profile;
@@ -37,7 +38,7 @@ export function RangeSettingsTab({
                   <SelectValue />
                 </SelectTrigger>
                 <SelectContent>
-                  {ORDER_RANGE_OPTIONS.map((option) => (
+                  {BUY_ORDER_RANGE_OPTIONS.map((option) => (
                     <SelectItem
                       key={option.value}
                       value={option.value.toString()}
@@ -72,6 +73,36 @@ export function RangeSettingsTab({
                 </SelectContent>
               </Select>
             </div>
+
+            <div className="space-y-2">
+              <Label htmlFor="sell-station-id">Selling Station ID</Label>
+              <Input
+                id="sell-station-id"
+                type="number"
+                placeholder="e.g. 60003760"
+                value={profile.sellStationId ?? ''}
+                onChange={(e) =>
+                  updateField(
+                    'sellStationId',
+                    e.target.value ? parseInt(e.target.value, 10) || null : null
+                  )
+                }
+              />
+            </div>
+
+            <div className="space-y-2">
+              <Label htmlFor="units-to-sell">Units to Sell</Label>
+              <Input
+                id="units-to-sell"
+                type="number"
+                min="1"
+                step="1"
+                value={profile.unitsToSell}
+                onChange={(e) =>
+                  updateField('unitsToSell', parseFloat(e.target.value) || 1)
+                }
+              />
+            </div>
           </div>
         </CardContent>
       </Card>
@@ -1,8 +1,10 @@
 import { Button } from './ui/button';
-import { Pin, HelpCircle, Folder } from 'lucide-react';
+import { Pin, HelpCircle, Folder, Pause, Play } from 'lucide-react';
 import { useState, useEffect } from 'react';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { WatcherStatus } from '@/lib/types';
 
 interface StatusBarProps {
   status: string;
@@ -10,6 +12,7 @@ interface StatusBarProps {
 
 export function StatusBar({ status }: StatusBarProps) {
   const [isPinned, setIsPinned] = useState(false);
+  const [watcherStatus, setWatcherStatus] = useState<WatcherStatus | null>(null);
 
   useEffect(() => {
     const initializePinState = async () => {
@@ -22,6 +25,14 @@ export function StatusBar({ status }: StatusBarProps) {
       }
     };
     initializePinState();
+
+    invoke<WatcherStatus>('watcher_status').then(setWatcherStatus).catch(console.error);
+    const unlisten = listen<WatcherStatus>('watcher-status', (event) => {
+      setWatcherStatus(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
   }, []);
 
   const handlePin = async () => {
@@ -39,6 +50,17 @@ export function StatusBar({ status }: StatusBarProps) {
     await invoke('select_log_path');
   };
 
+  const isPaused = watcherStatus?.state === 'paused';
+
+  const handleTogglePause = async () => {
+    try {
+      const updated = await invoke<WatcherStatus>(isPaused ? 'resume_watcher' : 'pause_watcher');
+      setWatcherStatus(updated);
+    } catch (error) {
+      console.error('Failed to pause or resume the watcher:', error);
+    }
+  };
+
   return (
     <div className="flex items-center justify-between border-t bg-muted/50 px-4 py-2 text-sm">
       <div className="flex items-center gap-2">
@@ -58,6 +80,20 @@ export function StatusBar({ status }: StatusBarProps) {
         >
           <Folder className="h-4 w-4" />
         </Button>
+        <Button
+          size="sm"
+          variant="ghost"
+          onClick={handleTogglePause}
+          title={
+            isPaused
+              ? 'Resume processing market logs'
+              : `Pause processing market logs (${watcherStatus?.filesProcessed ?? 0} processed${
+                  watcherStatus?.lastError ? `, last error: ${watcherStatus.lastError}` : ''
+                })`
+          }
+        >
+          {isPaused ? <Play className="h-4 w-4" /> : <Pause className="h-4 w-4" />}
+        </Button>
         <Button size="sm" variant="ghost" title="This project was inspred by Elinor-Reloaded">
           <HelpCircle className="h-4 w-4" />
         </Button>
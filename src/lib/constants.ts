@@ -10,6 +10,11 @@ export const ORDER_RANGE_OPTIONS = [
   { value: OrderRange.REGION, label: 'Region' },
 ];
 
+export const BUY_ORDER_RANGE_OPTIONS = [
+  ...ORDER_RANGE_OPTIONS,
+  { value: OrderRange.REACHABLE, label: 'Reachable from station' },
+];
+
 export function getDefaultProfile() {
   return {
     charId: 0,
@@ -26,5 +31,14 @@ export function getDefaultProfile() {
     sellCustomBroker: 0.01,
     buyRange: OrderRange.HUB,
     sellRange: OrderRange.HUB,
+    depthTarget: { kind: 'depthPercent' as const, value: 5 },
+    priceEstimator: { kind: 'normalCi' as const },
+    buyHubGroup: 'Trade Hubs',
+    sellHubGroup: 'Trade Hubs',
+    sellStationId: null,
+    unitsToSell: 1,
+    minVolumeHandling: 'exclude' as const,
+    maxOrderAgeDays: null,
+    staleAfterMinutes: 15,
   };
 }
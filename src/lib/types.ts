@@ -4,6 +4,7 @@ export enum OrderRange {
   ONEJUMP = 2,
   TWOJUMP = 3,
   REGION = 4,
+  REACHABLE = 5,
 }
 
 export interface Profile {
@@ -21,21 +22,125 @@ export interface Profile {
   sellCustomBroker: number;
   buyRange: OrderRange;
   sellRange: OrderRange;
+  depthTarget: DepthTarget;
+  priceEstimator: PriceEstimator;
+  buyHubGroup: string;
+  sellHubGroup: string;
+  sellStationId: number | null;
+  unitsToSell: number;
+  minVolumeHandling: 'exclude' | 'flag';
+  maxOrderAgeDays: number | null;
+  staleAfterMinutes: number;
+}
+
+export interface StationGroup {
+  name: string;
+  stationIds: number[];
+}
+
+export type PriceEstimator =
+  | { kind: 'normalCi' }
+  | { kind: 'tDistributionCi' }
+  | { kind: 'median' }
+  | { kind: 'percentile'; percentile: number }
+  | { kind: 'trimmedMean'; trimPercent: number }
+  | { kind: 'medianAbsoluteDeviation'; threshold: number }
+  | { kind: 'volumeWeightedQuantile'; quantile: number };
+
+export interface PriceStatistics {
+  count: number;
+  mean: number;
+  stdDev: number;
+  median: number;
+  p10: number;
+  p25: number;
+  p75: number;
+  p90: number;
+  trimmedMean10: number;
+  medianAbsoluteDeviation: number;
+  volumeWeightedMedian: number;
+  tCiLower: number;
+  tCiUpper: number;
+}
+
+export type DepthTarget =
+  | { kind: 'quantity'; value: number }
+  | { kind: 'depthPercent'; value: number };
+
+export interface DepthPrice {
+  targetQuantity: number;
+  filledQuantity: number;
+  averagePrice: number;
+  worstPrice: number;
+  totalIskValue: number;
+  fullyFilled: boolean;
+}
+
+export interface DepthPrices {
+  target: DepthTarget;
+  sell: DepthPrice | null;
+  buy: DepthPrice | null;
 }
 
 export interface MarketData {
   itemName: string;
   typeId: number;
+  region: string | null;
+  exportedAt: string | null;
   sellPrice: number;
   buyPrice: number;
+  suggestedSellPrice: number;
+  suggestedBuyPrice: number;
   sellOrderCount: number;
   buyOrderCount: number;
   sellPrice95Ci: number;
   buyPrice95Ci: number;
+  /** The 95% CI prices on EVE's tick grid, falling back to the suggested prices. */
+  suggestedSellPrice95Ci: number;
+  suggestedBuyPrice95Ci: number;
   sellTotalQuantity: number;
   buyTotalQuantity: number;
   sellTotalIskValue: number;
   buyTotalIskValue: number;
+  depth: DepthPrices;
+  sellPriceStatistics: PriceStatistics | null;
+  buyPriceStatistics: PriceStatistics | null;
+  priceEstimator: PriceEstimator;
+  sellPriceEstimate: number;
+  buyPriceEstimate: number;
+  buyReachability: BuyReachabilitySummary | null;
+  minVolumeBlockedCount: number;
+  minVolumeBlockedBestPrice: number;
+  oldOrderCount: number;
+  exportAgeMinutes: number | null;
+  isStale: boolean;
+  stations: StationSummary[];
+  calculated: CalculatedData;
+  warnings: string[];
+  /** Set on `market-data` events: the watched directory the export came from. */
+  source?: LogSource;
+}
+
+export interface StationSummary {
+  stationId: number;
+  solarSystemId: number;
+  jumps: number;
+  bestBid: number;
+  bestAsk: number;
+  buyOrderCount: number;
+  sellOrderCount: number;
+  buyTotalQuantity: number;
+  sellTotalQuantity: number;
+  buyTotalIskValue: number;
+  sellTotalIskValue: number;
+}
+
+export interface BuyReachabilitySummary {
+  stationId: number;
+  stationFound: boolean;
+  reachable: number;
+  uncertain: number;
+  unreachable: number;
 }
 
 export interface CalculatedData {
@@ -47,3 +152,158 @@ export interface CalculatedData {
   buyOrderCost: number;
   sellOrderCost: number;
 }
+
+export interface OrderRow {
+  price: number;
+  volRemaining: number;
+  typeId: number;
+  range: number;
+  orderId: number;
+  volEntered: number;
+  minVolume: number;
+  bid: boolean;
+  issueDate: string | null;
+  duration: number;
+  stationId: number;
+  regionId: number;
+  solarSystemId: number;
+  jumps: number;
+  expiresAt: string | null;
+  ageDays: number | null;
+  expiresInDays: number | null;
+}
+
+export interface OrderBook {
+  itemName: string;
+  typeId: number;
+  region: string | null;
+  exportedAt: string | null;
+  orders: OrderRow[];
+}
+
+export interface RowRejection {
+  line: number;
+  reason: { kind: string; [field: string]: unknown };
+  message: string;
+}
+
+export interface ParseReport {
+  fileName: string;
+  totalRows: number;
+  parsedRows: number;
+  skippedRows: number;
+  rejections: RowRejection[];
+  warnings: string[];
+  error: string | null;
+  /** Set on `parse-report` events: the watched directory the export came from. */
+  source?: LogSource;
+}
+
+export interface ArbitrageOpportunity {
+  buyFromOrderId: number;
+  buyFromStationId: number;
+  buyPrice: number;
+  sellToOrderId: number;
+  sellToStationId: number;
+  sellPrice: number;
+  sellAtStationId: number;
+  quantity: number;
+  minJumps: number;
+  maxJumps: number;
+  brokerFee: number;
+  salesTax: number;
+  netProfit: number;
+  netProfitPerUnit: number;
+}
+
+export type OrderChangeKind = 'new' | 'vanished' | 'priceChanged' | 'volumeDecreased';
+
+export interface OrderChange {
+  orderId: number;
+  kind: OrderChangeKind;
+  bid: boolean;
+  stationId: number;
+  previousPrice: number | null;
+  price: number | null;
+  previousVolume: number | null;
+  volume: number | null;
+  tradedVolume: number;
+  expired: boolean;
+}
+
+export interface PriceMove {
+  previousPrice: number;
+  price: number;
+  cause: 'newOrder' | 'repriced' | 'bestOrderRemoved';
+  orderId: number;
+  stationId: number;
+}
+
+export interface SideDiff {
+  newOrders: number;
+  vanishedOrders: number;
+  priceChanges: number;
+  volumeDecreases: number;
+  tradedVolume: number;
+  vanishedVolume: number;
+  priceMove: PriceMove | null;
+}
+
+export interface MarketDiff {
+  itemName: string;
+  typeId: number;
+  region: string | null;
+  previousExportedAt: string | null;
+  exportedAt: string | null;
+  elapsedMinutes: number | null;
+  sell: SideDiff;
+  buy: SideDiff;
+  changes: OrderChange[];
+  /** Set on `market-diff` events: the watched directory the export came from. */
+  source?: LogSource;
+}
+
+export interface LogSource {
+  dir: string;
+  profile: string | null;
+}
+
+export interface WatchedDir {
+  path: string;
+  profile: string | null;
+}
+
+export interface SessionEntry {
+  fileName: string;
+  marketData: MarketData;
+  source: LogSource;
+}
+
+export type LogDirOrigin = 'windows' | 'steamProton' | 'winePrefix' | 'native';
+
+export interface LogDirCandidate {
+  path: string;
+  origin: LogDirOrigin;
+  marketLogCount: number;
+  recentMarketLogCount: number;
+  lastExportedAt: string | null;
+}
+
+export type WatcherBackend = 'native' | 'polling';
+
+export interface WatcherBackendStatus {
+  backend: WatcherBackend;
+  pollIntervalSecs: number | null;
+  fallbackReason: string | null;
+}
+
+export type WatcherState = 'starting' | 'waiting' | 'watching' | 'paused';
+
+export interface WatcherStatus {
+  state: WatcherState;
+  backend: WatcherBackend | null;
+  watchedPaths: string[];
+  filesProcessed: number;
+  lastEventAt: string | null;
+  lastError: string | null;
+}